@@ -59,6 +59,31 @@ fn bench_zip_templates_from_vec(c: &mut Criterion) {
     });
 }
 
+fn bench_large_static(c: &mut Criterion) {
+    // A 100KB page with a single placeholder: dominated by copying statics.
+    let template = format!(
+        "<html><body>{}{{{{content}}}}</body></html>",
+        "<p>lorem ipsum dolor sit amet</p>".repeat(100 * 1024 / 32)
+    );
+    let parsed = ZipTemplate::parse(&template);
+
+    let mut flat = rustc_hash::FxHashMap::default();
+    flat.insert("content".to_string(), "Hello".to_string());
+
+    let mut group = c.benchmark_group("large_static_100kb");
+    group.bench_function("zip_templates::render", |b| {
+        b.iter(|| black_box(parsed.render(&flat)))
+    });
+    group.bench_function("zip_templates::render_bulk", |b| {
+        b.iter(|| black_box(parsed.render_bulk(&flat)))
+    });
+    let mut scratch = Vec::new();
+    group.bench_function("zip_templates::render_with_scratch", |b| {
+        b.iter(|| black_box(parsed.render_with_scratch(&flat, &mut scratch)))
+    });
+    group.finish();
+}
+
 fn bench_tera(c: &mut Criterion) {
     let (template, data) = prepare_data();
 
@@ -157,6 +182,7 @@ criterion_group!(
     bench_zip_templates,
     bench_zip_templates_flat,
     bench_zip_templates_from_vec,
+    bench_large_static,
     bench_tera,
     bench_mystical_runic,
     bench_simple_replace,
@@ -0,0 +1,100 @@
+//! Templates that learn their output buffer size.
+//!
+//! The `pre_emptive_size` given at parse time is a guess. An [`AdaptiveTemplate`]
+//! replaces it with the largest output seen so far, so once every output size has
+//! been observed a render allocates its buffer once and never grows it.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rustc_hash::FxHashMap;
+
+use crate::ZipTemplate;
+
+/// A template that sizes its output buffer from the outputs it has rendered.
+///
+/// The capacity starts at the template's `pre_emptive_size` and is raised to the
+/// length of any larger output; it never shrinks. Renders take `&self`, so one
+/// instance can be shared between threads.
+///
+/// # Examples
+///
+/// ```
+/// use zip_templates::{AdaptiveTemplate, ZipTemplate};
+/// use rustc_hash::FxHashMap;
+///
+/// let template = AdaptiveTemplate::new(ZipTemplate::parse_with_capacity("Hello, {{name}}!", 0));
+/// let mut values = FxHashMap::default();
+/// values.insert("name".to_string(), "World".to_string());
+///
+/// assert_eq!(template.render(&values), "Hello, World!");
+/// assert_eq!(template.capacity(), 13);
+/// ```
+#[derive(Debug)]
+pub struct AdaptiveTemplate {
+    template: ZipTemplate,
+    capacity: AtomicUsize,
+}
+
+impl AdaptiveTemplate {
+    /// Wraps `template`, starting from its `pre_emptive_size`.
+    pub fn new(template: ZipTemplate) -> Self {
+        let capacity = AtomicUsize::new(template.pre_emptive_size);
+        AdaptiveTemplate { template, capacity }
+    }
+
+    /// The wrapped template.
+    pub fn template(&self) -> &ZipTemplate {
+        &self.template
+    }
+
+    /// The capacity the next render will allocate.
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Renders like [`ZipTemplate::render`], then raises the capacity to the output
+    /// length if it was larger.
+    pub fn render(&self, flat: &FxHashMap<String, String>) -> String {
+        let mut out = String::with_capacity(self.capacity());
+
+        let resolver = self.template.resolver(flat);
+        for (s, placeholder) in self
+            .template
+            .statics
+            .iter()
+            .zip(self.template.placeholders.iter())
+        {
+            out.push_str(s);
+            out.push_str(&resolver.get(placeholder));
+        }
+
+        self.capacity.fetch_max(out.len(), Ordering::Relaxed);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capacity_converges_to_largest_output() {
+        let template = AdaptiveTemplate::new(ZipTemplate::parse_with_capacity("<{{body}}>", 4));
+        let mut values = FxHashMap::default();
+
+        for len in [3, 40, 12, 25] {
+            values.insert("body".to_string(), "x".repeat(len));
+            assert_eq!(template.render(&values).len(), len + 2);
+        }
+        assert_eq!(template.capacity(), 42);
+
+        // After warm-up every output fits in the initial allocation.
+        for len in [40, 1, 39] {
+            values.insert("body".to_string(), "x".repeat(len));
+            let out = template.render(&values);
+            assert!(out.capacity() >= 42);
+            assert!(out.len() <= template.capacity());
+        }
+        assert_eq!(template.capacity(), 42);
+    }
+}
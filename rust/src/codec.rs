@@ -0,0 +1,326 @@
+//! Compact binary encoding of parsed templates.
+//!
+//! Layout (all integers little-endian):
+//!
+//! ```text
+//! magic: u8 = 0x5A | version: u8 = 1 | pre_emptive_size: u64
+//! statics: u32 count, then per segment u32 byte length + UTF-8 bytes
+//! placeholders: u32 count, then per key u32 byte length + UTF-8 bytes
+//! ```
+
+use std::fmt;
+
+use crate::ZipTemplate;
+
+const MAGIC: u8 = 0x5A;
+const VERSION: u8 = 1;
+
+/// Error returned by [`ZipTemplate::from_bytes`] for blobs it cannot decode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The blob does not start with the template magic byte.
+    BadMagic,
+    /// The blob was written by an unknown format version.
+    UnsupportedVersion(u8),
+    /// The blob ended in the middle of a field.
+    UnexpectedEof,
+    /// A segment is not valid UTF-8.
+    InvalidUtf8,
+    /// Bytes remain after the last placeholder.
+    TrailingBytes,
+    /// The blob has no statics, not as many placeholders as statics, or a non-empty
+    /// last placeholder where the alignment slot belongs.
+    Misaligned { statics: usize, placeholders: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::BadMagic => write!(f, "not a zip template blob"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            DecodeError::UnexpectedEof => write!(f, "unexpected end of blob"),
+            DecodeError::InvalidUtf8 => write!(f, "segment is not valid UTF-8"),
+            DecodeError::TrailingBytes => write!(f, "trailing bytes after template"),
+            DecodeError::Misaligned {
+                statics,
+                placeholders,
+            } => write!(
+                f,
+                "{} statics and {} placeholders do not form a template",
+                statics, placeholders
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < n {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn strings(&mut self) -> Result<Vec<String>, DecodeError> {
+        let count = self.u32()? as usize;
+        // Every entry takes at least its 4-byte length, so a corrupt count cannot
+        // make us reserve more than the blob could possibly hold.
+        let mut out = Vec::with_capacity(count.min(self.bytes.len() / 4));
+        for _ in 0..count {
+            let len = self.u32()? as usize;
+            let bytes = self.take(len)?;
+            let s = std::str::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8)?;
+            out.push(s.to_string());
+        }
+        Ok(out)
+    }
+}
+
+fn write_strings(out: &mut Vec<u8>, strings: &[String]) {
+    out.extend_from_slice(&encode_len(strings.len()).to_le_bytes());
+    for s in strings {
+        out.extend_from_slice(&encode_len(s.len()).to_le_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+}
+
+/// Converts a count or byte length to its `u32` field.
+fn encode_len(len: usize) -> u32 {
+    u32::try_from(len).expect("segment count or length does not fit the blob format")
+}
+
+impl ZipTemplate {
+    /// Encodes the parsed template into a compact, versioned byte blob.
+    ///
+    /// The blob can be embedded in a binary and turned back into a template with
+    /// [`ZipTemplate::from_bytes`] without re-parsing the source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the template has `u32::MAX` or more segments, or a segment of 4 GiB
+    /// or more, which the format can not represent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse("Hello {{name}}!");
+    /// let decoded = ZipTemplate::from_bytes(&template.to_bytes()).unwrap();
+    /// assert_eq!(decoded.statics, template.statics);
+    /// assert_eq!(decoded.placeholders, template.placeholders);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let payload: usize = self
+            .statics
+            .iter()
+            .chain(self.placeholders.iter())
+            .map(|s| s.len() + 4)
+            .sum();
+        let mut out = Vec::with_capacity(2 + 8 + 4 + 4 + payload);
+
+        out.push(MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&(self.pre_emptive_size as u64).to_le_bytes());
+        write_strings(&mut out, &self.statics);
+        write_strings(&mut out, &self.placeholders);
+
+        out
+    }
+
+    /// Decodes a template previously encoded with [`ZipTemplate::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DecodeError`] if the blob is truncated, corrupt, was written by
+    /// an unsupported format version or does not hold exactly one placeholder per
+    /// static. Decoding never panics on malformed input.
+    ///
+    /// The capacity hint is taken from the blob unless it is more than twice the
+    /// blob's size, in which case it is recomputed from the decoded segments, so a
+    /// corrupt hint can not make rendering allocate without bound.
+    pub fn from_bytes(bytes: &[u8]) -> Result<ZipTemplate, DecodeError> {
+        let mut reader = Reader { bytes };
+
+        if reader.u8()? != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        let pre_emptive_size = usize::try_from(reader.u64()?).unwrap_or(usize::MAX);
+        let statics = reader.strings()?;
+        let placeholders = reader.strings()?;
+
+        if !reader.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+        if statics.is_empty()
+            || statics.len() != placeholders.len()
+            || placeholders.last().is_some_and(|last| !last.is_empty())
+        {
+            return Err(DecodeError::Misaligned {
+                statics: statics.len(),
+                placeholders: placeholders.len(),
+            });
+        }
+
+        let pre_emptive_size = match pre_emptive_size > bytes.len().saturating_mul(2) {
+            true => {
+                let len: usize = statics.iter().chain(&placeholders).map(String::len).sum();
+                len * 3 / 2
+            }
+            false => pre_emptive_size,
+        };
+
+        Ok(ZipTemplate {
+            statics,
+            placeholders,
+            pre_emptive_size,
+            computed: Default::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hash::FxHashMap;
+
+    #[test]
+    fn round_trip() {
+        for tpl in [
+            "",
+            "static text only",
+            "{{a}},{{b}},{{c}}",
+            "Hi, {{user.name.first}} — balance: {{account.balance}} USD",
+        ] {
+            let parsed = ZipTemplate::parse(tpl);
+            let decoded = ZipTemplate::from_bytes(&parsed.to_bytes()).unwrap();
+            assert_eq!(decoded.statics, parsed.statics);
+            assert_eq!(decoded.placeholders, parsed.placeholders);
+            assert_eq!(decoded.pre_emptive_size, parsed.pre_emptive_size);
+        }
+    }
+
+    #[test]
+    fn round_trip_renders() {
+        let parsed = ZipTemplate::parse("Hello, {{name}}!");
+        let decoded = ZipTemplate::from_bytes(&parsed.to_bytes()).unwrap();
+        let mut flat = FxHashMap::default();
+        flat.insert("name".to_string(), "World".to_string());
+        assert_eq!(decoded.render(&flat), "Hello, World!");
+    }
+
+    #[test]
+    fn truncated_blob_errors() {
+        let bytes = ZipTemplate::parse("Hello, {{name}}!").to_bytes();
+        for len in 0..bytes.len() {
+            assert!(ZipTemplate::from_bytes(&bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn corrupt_blob_errors() {
+        let mut bytes = ZipTemplate::parse("Hello, {{name}}!").to_bytes();
+
+        assert_eq!(
+            ZipTemplate::from_bytes(&[0x00, VERSION]).unwrap_err(),
+            DecodeError::BadMagic
+        );
+        assert_eq!(
+            ZipTemplate::from_bytes(&[MAGIC, 9]).unwrap_err(),
+            DecodeError::UnsupportedVersion(9)
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            ZipTemplate::from_bytes(&trailing).unwrap_err(),
+            DecodeError::TrailingBytes
+        );
+
+        // Claim an absurd number of statics.
+        bytes[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            ZipTemplate::from_bytes(&bytes).unwrap_err(),
+            DecodeError::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn misaligned_blob_errors() {
+        let blob = |statics: &[&str], placeholders: &[&str]| {
+            let mut out = vec![MAGIC, VERSION];
+            out.extend_from_slice(&0u64.to_le_bytes());
+            let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            write_strings(&mut out, &strings(statics));
+            write_strings(&mut out, &strings(placeholders));
+            out
+        };
+
+        assert_eq!(
+            ZipTemplate::from_bytes(&blob(&[], &[])).unwrap_err(),
+            DecodeError::Misaligned {
+                statics: 0,
+                placeholders: 0
+            }
+        );
+        assert_eq!(
+            ZipTemplate::from_bytes(&blob(&["a", "b"], &["x"])).unwrap_err(),
+            DecodeError::Misaligned {
+                statics: 2,
+                placeholders: 1
+            }
+        );
+        assert_eq!(
+            ZipTemplate::from_bytes(&blob(&["a"], &["x", ""])).unwrap_err(),
+            DecodeError::Misaligned {
+                statics: 1,
+                placeholders: 2
+            }
+        );
+        assert_eq!(
+            ZipTemplate::from_bytes(&blob(&["a", "b"], &["x", "y"])).unwrap_err(),
+            DecodeError::Misaligned {
+                statics: 2,
+                placeholders: 2
+            }
+        );
+        assert!(ZipTemplate::from_bytes(&blob(&["a", "b"], &["x", ""])).is_ok());
+    }
+
+    #[test]
+    fn corrupt_capacity_hint_is_recomputed() {
+        let mut bytes = ZipTemplate::parse("Hello, {{name}}!").to_bytes();
+        bytes[2..10].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let decoded = ZipTemplate::from_bytes(&bytes).unwrap();
+        assert!(decoded.pre_emptive_size <= 2 * bytes.len());
+        let mut flat = FxHashMap::default();
+        flat.insert("name".to_string(), "World".to_string());
+        assert_eq!(decoded.render(&flat), "Hello, World!");
+    }
+}
@@ -0,0 +1,78 @@
+//! Compressed rendering.
+//!
+//! Rendering a response and then compressing it needs the whole output as a
+//! `String` first. Here the segments are written straight into the encoder.
+
+use std::io;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rustc_hash::FxHashMap;
+
+use crate::ZipTemplate;
+
+impl ZipTemplate {
+    /// Renders a template directly into a gzip stream, returning the compressed bytes.
+    ///
+    /// Segments are fed to the encoder as they are produced, as with
+    /// [`ZipTemplate::write_to_flushing`], so the uncompressed output is never held
+    /// in memory as a whole.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error reported by the encoder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// use flate2::read::GzDecoder;
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let compressed = template.render_gzip(&values).unwrap();
+    /// let mut rendered = String::new();
+    /// GzDecoder::new(&compressed[..]).read_to_string(&mut rendered).unwrap();
+    /// assert_eq!(rendered, "Hello, World!");
+    /// ```
+    pub fn render_gzip(&self, flat: &FxHashMap<String, String>) -> io::Result<Vec<u8>> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        self.write_to_flushing(flat, &mut encoder, 0)?;
+        encoder.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    #[test]
+    fn gzip_round_trip() {
+        let parsed = ZipTemplate::parse(
+            "<tr><td>{{id}}</td><td>{{name}}</td></tr>\n"
+                .repeat(50)
+                .as_str(),
+        );
+        let mut values = FxHashMap::default();
+        values.insert("id".to_string(), "42".to_string());
+        values.insert("name".to_string(), "Sam & co".to_string());
+
+        let compressed = parsed.render_gzip(&values).unwrap();
+        let rendered = parsed.render(&values);
+        assert!(compressed.len() < rendered.len());
+
+        let mut decompressed = String::new();
+        GzDecoder::new(&compressed[..])
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, rendered);
+    }
+}
@@ -0,0 +1,242 @@
+//! Values computed by functions registered on a template.
+//!
+//! Some values, such as `{{current.load}}` on a dashboard, are not data but the
+//! result of a call. Registering the function on the template once lets
+//! [`ZipTemplate::render`] compute the value on every render.
+//!
+//! Renderers resolve placeholders through a [`Resolver`], which consults the
+//! registered functions before the data map and caches their results for the
+//! duration of one render.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::Arc;
+
+use rustc_hash::FxHashMap;
+
+use crate::tag::Tag;
+use crate::{is_resolved_with, lookup, resolve, ZipTemplate};
+
+type ComputedFn = dyn Fn() -> String + Send + Sync;
+
+/// The functions registered with [`ZipTemplate::register_computed`], by key.
+#[derive(Clone, Default)]
+pub(crate) struct Computed(FxHashMap<String, Arc<ComputedFn>>);
+
+/// Resolves placeholders for one render: registered functions first, then `flat`.
+pub(crate) struct Resolver<'a, V> {
+    computed: &'a FxHashMap<String, Arc<ComputedFn>>,
+    flat: &'a FxHashMap<String, V>,
+    cache: RefCell<FxHashMap<&'a str, String>>,
+}
+
+impl<'a, V: AsRef<str>> Resolver<'a, V> {
+    /// The value of the single key `key`, if it is computed or in the map.
+    fn value(&self, key: &str) -> Option<Cow<'a, str>> {
+        match self.computed.get_key_value(key) {
+            Some((name, f)) => {
+                let mut cache = self.cache.borrow_mut();
+                Some(Cow::Owned(cache.entry(name).or_insert_with(|| f()).clone()))
+            }
+            None => self
+                .flat
+                .get(key)
+                .map(|value| Cow::Borrowed(value.as_ref())),
+        }
+    }
+
+    /// Resolves `placeholder` like [`lookup`], with computed keys taking precedence
+    /// over the map. Each function is called at most once per resolver.
+    pub fn get<'k>(&self, placeholder: &'k str) -> Cow<'k, str>
+    where
+        'a: 'k,
+    {
+        if self.computed.is_empty() {
+            return lookup(self.flat, placeholder);
+        }
+        resolve(placeholder, |key| self.value(key))
+    }
+
+    /// Resolves `placeholder` like [`Resolver::get`], consulting `over` for each key
+    /// after the computed values and before the map.
+    pub fn get_over<'k>(
+        &self,
+        placeholder: &'k str,
+        over: impl Fn(&str) -> Option<&'k str>,
+    ) -> Cow<'k, str>
+    where
+        'a: 'k,
+    {
+        resolve(placeholder, |key| match self.computed.contains_key(key) {
+            true => self.value(key),
+            false => over(key).map(Cow::Borrowed).or_else(|| self.value(key)),
+        })
+    }
+
+    /// Resolves `placeholder` like [`Resolver::get`], consulting `fallback` for
+    /// each key that is neither computed nor in the map.
+    pub fn get_or<'k>(
+        &self,
+        placeholder: &'k str,
+        fallback: impl Fn(&str) -> Option<Cow<'k, str>>,
+    ) -> Cow<'k, str>
+    where
+        'a: 'k,
+    {
+        resolve(placeholder, |key| self.value(key).or_else(|| fallback(key)))
+    }
+
+    /// Returns `true` if the key of `placeholder`, or a key of its `??` chain, is
+    /// computed or in the map. Unlike [`Resolver::is_resolved`], a `|default` does
+    /// not count.
+    pub fn has_key(&self, placeholder: &str) -> bool {
+        let contains = |key: &str| self.computed.contains_key(key) || self.flat.contains_key(key);
+        contains(placeholder)
+            || (placeholder.contains(['?', '|', ':', '\\'])
+                && Tag::parse(placeholder).keys().any(|key| contains(&key)))
+    }
+
+    /// Returns `true` if `placeholder` resolves, counting computed keys as present.
+    pub fn is_resolved(&self, placeholder: &str) -> bool {
+        is_resolved_with(placeholder, |key| {
+            self.computed.contains_key(key) || self.flat.contains_key(key)
+        })
+    }
+}
+
+impl fmt::Debug for Computed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl ZipTemplate {
+    /// Registers `f` to compute the value of the placeholder `key`.
+    ///
+    /// Renderers call `f` instead of looking `key` up in the data map, including
+    /// where `key` is part of a `??` chain. Each function is called at most once per
+    /// render, even if its placeholder appears several times, and
+    /// [`ZipTemplate::fill`] never binds a computed key. Registering a key again
+    /// replaces its function.
+    ///
+    /// Only renderers reading a key-value map see computed values:
+    /// [`ZipTemplate::render_json`], [`ZipTemplate::render_json_with`],
+    /// [`ZipTemplate::render_from_vec`] and the renderers taking their values from
+    /// callbacks ignore them.
+    ///
+    /// `f` must be `Send + Sync`, which is narrower than any `Fn() -> String`: the
+    /// functions are stored in the template, and `ZipTemplate` has always been
+    /// `Send + Sync` so it can be shared between threads or kept in a `static`.
+    /// Capture shared state through `Arc` and atomics or a `Mutex` rather than `Rc`
+    /// and `Cell`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let mut template = ZipTemplate::parse("{{user}}: {{answer}}");
+    /// template.register_computed("answer", Box::new(|| (6 * 7).to_string()));
+    ///
+    /// let mut values = FxHashMap::default();
+    /// values.insert("user".to_string(), "Sam".to_string());
+    /// assert_eq!(template.render(&values), "Sam: 42");
+    /// ```
+    pub fn register_computed(&mut self, key: &str, f: Box<ComputedFn>) {
+        self.computed.0.insert(key.to_string(), Arc::from(f));
+    }
+
+    /// A resolver for one render against `flat`.
+    pub(crate) fn resolver<'a, V>(&'a self, flat: &'a FxHashMap<String, V>) -> Resolver<'a, V> {
+        Resolver {
+            computed: &self.computed.0,
+            flat,
+            cache: RefCell::default(),
+        }
+    }
+
+    /// Returns `true` if `key` has a registered function.
+    pub(crate) fn is_computed(&self, key: &str) -> bool {
+        self.computed.0.contains_key(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn computed_once_per_render() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+
+        let mut parsed =
+            ZipTemplate::parse("load {{current.load}} / again {{current.load}} on {{host}}");
+        parsed.register_computed(
+            "current.load",
+            Box::new(move || {
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                format!("{}%", n * 10)
+            }),
+        );
+
+        let mut values = FxHashMap::default();
+        values.insert("host".to_string(), "db1".to_string());
+        values.insert("current.load".to_string(), "ignored".to_string());
+
+        assert_eq!(parsed.render(&values), "load 10% / again 10% on db1");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(parsed.render(&values), "load 20% / again 20% on db1");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Clones share the registered functions.
+        let cloned = parsed.clone();
+        assert_eq!(cloned.render(&values), "load 30% / again 30% on db1");
+    }
+
+    #[test]
+    fn template_stays_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ZipTemplate>();
+    }
+
+    #[test]
+    fn computed_values_in_every_renderer() {
+        let mut parsed =
+            ZipTemplate::parse("<{{value}}> {{current.load | n/a}} {{missing ?? value}}");
+        parsed.register_computed("value", Box::new(|| "computed".to_string()));
+        parsed.register_computed("current.load", Box::new(|| "5%".to_string()));
+
+        let mut values = FxHashMap::default();
+        values.insert("value".to_string(), "data".to_string());
+
+        let expected = "<computed> 5% computed";
+        assert_eq!(parsed.render(&values), expected);
+        assert_eq!(parsed.render_bulk(&values), expected);
+        assert_eq!(
+            parsed.render_with_scratch(&values, &mut Vec::new()),
+            expected
+        );
+        assert_eq!(
+            parsed.render_range(&values, 0..parsed.statics.len()),
+            expected
+        );
+        assert_eq!(parsed.render_xml_escaped(&values), "<computed> 5% computed");
+        let mut out = String::new();
+        parsed.render_into(&values, &mut out).unwrap();
+        assert_eq!(out, expected);
+        assert_eq!(
+            parsed.render_strict(&FxHashMap::default()),
+            Ok(expected.to_string())
+        );
+
+        // Filling with a map value does not bake it over the function.
+        let filled = parsed.fill(&values);
+        assert_eq!(filled.placeholders.len(), parsed.placeholders.len());
+        assert_eq!(filled.render(&FxHashMap::default()), expected);
+    }
+}
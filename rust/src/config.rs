@@ -0,0 +1,167 @@
+//! Parse settings shared by a whole application.
+//!
+//! A [`TemplateConfig`] gathers the delimiters and parse options once, so every
+//! template of an application is parsed the same way.
+
+use crate::{ParseError, ZipTemplate};
+
+/// Delimiters and options applied by [`TemplateConfig::parse`].
+///
+/// The default configuration parses exactly like [`ZipTemplate::parse`].
+///
+/// # Examples
+///
+/// ```
+/// use zip_templates::TemplateConfig;
+///
+/// let config = TemplateConfig::new().delimiters("<%", "%>").max_len(1024);
+/// let template = config.parse("Hello, <% name %>!").unwrap();
+/// assert_eq!(template.placeholders, ["name", ""]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateConfig {
+    open: String,
+    close: String,
+    lenient_tags: bool,
+    brace_escapes: bool,
+    max_len: Option<usize>,
+}
+
+impl Default for TemplateConfig {
+    fn default() -> Self {
+        TemplateConfig {
+            open: "{{".to_string(),
+            close: "}}".to_string(),
+            lenient_tags: false,
+            brace_escapes: false,
+            max_len: None,
+        }
+    }
+}
+
+impl TemplateConfig {
+    /// Creates a configuration with `{{` / `}}` delimiters and no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the delimiters that open and close a placeholder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either delimiter is empty.
+    pub fn delimiters(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.open = open.into();
+        self.close = close.into();
+        assert!(
+            !self.open.is_empty() && !self.close.is_empty(),
+            "delimiters must not be empty"
+        );
+        self
+    }
+
+    /// Keeps tags starting with `#`, `/`, `>`, `!` or `@` as static text, like
+    /// [`ZipTemplate::parse_lenient_tags`].
+    pub fn lenient_tags(mut self, lenient_tags: bool) -> Self {
+        self.lenient_tags = lenient_tags;
+        self
+    }
+
+    /// Treats an opener preceded by `\` as literal text rather than the start of a
+    /// tag, and unescapes `\{{` and `\}}` to `{{` and `}}` in the statics. With the
+    /// default delimiters this reads back the output of
+    /// [`ZipTemplate::render_escaping_braces`].
+    pub fn brace_escapes(mut self, brace_escapes: bool) -> Self {
+        self.brace_escapes = brace_escapes;
+        self
+    }
+
+    /// Rejects templates longer than `max_len` bytes, like
+    /// [`ZipTemplate::parse_bounded`].
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Parses `template` with these settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError::TooLong`] if a maximum length is set and `template`
+    /// exceeds it.
+    pub fn parse(&self, template: &str) -> Result<ZipTemplate, ParseError> {
+        if let Some(max) = self.max_len.filter(|&max| template.len() > max) {
+            return Err(ParseError::TooLong {
+                len: template.len(),
+                max,
+            });
+        }
+
+        ZipTemplate::scan(
+            template,
+            &[(&self.open, &self.close)],
+            self.brace_escapes,
+            (template.len() as f32 * 1.5) as usize,
+            |key, _, _| Ok(!self.lenient_tags || !key.starts_with(['#', '/', '>', '!', '@'])),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_hash::FxHashMap;
+
+    use super::*;
+
+    #[test]
+    fn custom_delimiters_for_several_templates() {
+        let config = TemplateConfig::new().delimiters("<%", "%>");
+
+        let page = config.parse("<h1><% title %></h1>{{ not a tag }}").unwrap();
+        assert_eq!(page.statics, ["<h1>", "</h1>{{ not a tag }}"]);
+        assert_eq!(page.placeholders, ["title", ""]);
+
+        let row = config.parse("<% id %>,<% name %>").unwrap();
+        assert_eq!(row.placeholders, ["id", "name", ""]);
+
+        let mut values = FxHashMap::default();
+        values.insert("id".to_string(), "7".to_string());
+        values.insert("name".to_string(), "Sam".to_string());
+        assert_eq!(row.render(&values), "7,Sam");
+    }
+
+    #[test]
+    fn default_matches_parse() {
+        let source = "a {{x}} b {{#each y}}";
+        let (configured, parsed) = (
+            TemplateConfig::new().parse(source).unwrap(),
+            ZipTemplate::parse(source),
+        );
+        assert_eq!(configured.statics, parsed.statics);
+        assert_eq!(configured.placeholders, parsed.placeholders);
+    }
+
+    #[test]
+    fn options() {
+        let config = TemplateConfig::new().lenient_tags(true).max_len(21);
+        let parsed = config.parse("{{#if x}}{{x}}{{/if}}").unwrap();
+        assert_eq!(parsed.statics, ["{{#if x}}", "{{/if}}"]);
+        assert_eq!(
+            config.parse("{{#if x}}{{x}}{{/if}} ").unwrap_err(),
+            ParseError::TooLong { len: 22, max: 21 }
+        );
+    }
+
+    #[test]
+    fn brace_escapes() {
+        let config = TemplateConfig::new().brace_escapes(true);
+        let parsed = config
+            .parse(r"literal \{{a\}} and {{b}}, \{{ open {{c}}")
+            .unwrap();
+        assert_eq!(parsed.statics, ["literal {{a}} and ", ", {{ open ", ""]);
+        assert_eq!(parsed.placeholders, ["b", "c", ""]);
+
+        let plain = TemplateConfig::new().parse(r"\{{a}}").unwrap();
+        assert_eq!(plain.statics, ["\\", ""]);
+    }
+}
@@ -0,0 +1,202 @@
+//! Escaping of values for XML and Prometheus output.
+//!
+//! Element content and attribute values need different escaping: inside an
+//! attribute, a literal tab or line break is normalized to a space by XML parsers,
+//! so those have to be written as character references too.
+//!
+//! Prometheus label values are quoted strings in the text exposition format, in
+//! which only `\`, `"` and line feeds have to be escaped.
+
+use std::borrow::Cow;
+
+use rustc_hash::FxHashMap;
+
+use crate::ZipTemplate;
+
+/// Escapes `s` for XML element content: `&`, `<`, `>`, `"` and `'` are replaced by
+/// entity references.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(zip_templates::xml_escape("a < b & 'c'"), "a &lt; b &amp; &apos;c&apos;");
+/// ```
+pub fn xml_escape(s: &str) -> Cow<'_, str> {
+    escape(s, false)
+}
+
+/// Escapes `s` for an XML attribute value: like [`xml_escape`], and additionally
+/// `\t`, `\n` and `\r` are replaced by character references so they survive
+/// attribute-value normalization.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(zip_templates::xml_attr_escape("line 1\nline \"2\""), "line 1&#10;line &quot;2&quot;");
+/// ```
+pub fn xml_attr_escape(s: &str) -> Cow<'_, str> {
+    escape(s, true)
+}
+
+fn escape(s: &str, attribute: bool) -> Cow<'_, str> {
+    let reference = |c: char| match c {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        '"' => Some("&quot;"),
+        '\'' => Some("&apos;"),
+        '\t' if attribute => Some("&#9;"),
+        '\n' if attribute => Some("&#10;"),
+        '\r' if attribute => Some("&#13;"),
+        _ => None,
+    };
+
+    let Some(first) = s.find(|c| reference(c).is_some()) else {
+        return Cow::Borrowed(s);
+    };
+
+    let mut out = String::with_capacity(s.len() + 16);
+    out.push_str(&s[..first]);
+    for c in s[first..].chars() {
+        match reference(c) {
+            Some(reference) => out.push_str(reference),
+            None => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Escapes `s` for a Prometheus label value: a backslash, a double quote and a
+/// line feed are written as `\\`, `\"` and `\n`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(zip_templates::prometheus_escape(r#"say "hi""#), r#"say \"hi\""#);
+/// ```
+pub fn prometheus_escape(s: &str) -> Cow<'_, str> {
+    let Some(first) = s.find(['\\', '"', '\n']) else {
+        return Cow::Borrowed(s);
+    };
+
+    let mut out = String::with_capacity(s.len() + 8);
+    out.push_str(&s[..first]);
+    for c in s[first..].chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+impl ZipTemplate {
+    /// Renders a template, escaping every value with [`xml_escape`].
+    ///
+    /// Statics are emitted unchanged. Values rendered inside attributes should
+    /// instead be escaped with [`xml_attr_escape`] before rendering with
+    /// [`ZipTemplate::render`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("<note>{{body}}</note>");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("body".to_string(), "1 < 2".to_string());
+    ///
+    /// assert_eq!(template.render_xml_escaped(&values), "<note>1 &lt; 2</note>");
+    /// ```
+    pub fn render_xml_escaped(&self, flat: &FxHashMap<String, String>) -> String {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        let resolver = self.resolver(flat);
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+            out.push_str(&xml_escape(&resolver.get(placeholder)));
+        }
+
+        out
+    }
+
+    /// Renders a template, escaping every value with [`prometheus_escape`].
+    ///
+    /// Statics, which hold the metric names, label names and quotes, are emitted
+    /// unchanged, so a value can not end its label early or split the line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse(r#"http_requests{path="{{path}}"} {{count}}"#);
+    /// let mut values = FxHashMap::default();
+    /// values.insert("path".to_string(), r#"/a"b"#.to_string());
+    /// values.insert("count".to_string(), "3".to_string());
+    ///
+    /// assert_eq!(
+    ///     template.render_prometheus_escaped(&values),
+    ///     r#"http_requests{path="/a\"b"} 3"#
+    /// );
+    /// ```
+    pub fn render_prometheus_escaped(&self, flat: &FxHashMap<String, String>) -> String {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        let resolver = self.resolver(flat);
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+            out.push_str(&prometheus_escape(&resolver.get(placeholder)));
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_content() {
+        let parsed = ZipTemplate::parse("<expr>{{lhs}}</expr>");
+        let mut values = FxHashMap::default();
+        values.insert("lhs".to_string(), "a<b && c>\"d\"\n".to_string());
+        assert_eq!(
+            parsed.render_xml_escaped(&values),
+            "<expr>a&lt;b &amp;&amp; c&gt;&quot;d&quot;\n</expr>"
+        );
+    }
+
+    #[test]
+    fn attribute_value() {
+        assert_eq!(
+            xml_attr_escape("first\r\nsecond\tthird <'x'>"),
+            "first&#13;&#10;second&#9;third &lt;&apos;x&apos;&gt;"
+        );
+        assert!(matches!(xml_attr_escape("plain"), Cow::Borrowed("plain")));
+        assert_eq!(xml_escape("a\nb"), "a\nb");
+    }
+
+    #[test]
+    fn prometheus_label_value() {
+        let parsed =
+            ZipTemplate::parse("http_requests{path=\"{{path}}\",code=\"{{code}}\"} {{count}}\n");
+        let mut values = FxHashMap::default();
+        values.insert("path".to_string(), "/say \"hi\"\nC:\\tmp".to_string());
+        values.insert("code".to_string(), "200".to_string());
+        values.insert("count".to_string(), "7".to_string());
+
+        let line = parsed.render_prometheus_escaped(&values);
+        assert_eq!(
+            line,
+            "http_requests{path=\"/say \\\"hi\\\"\\nC:\\\\tmp\",code=\"200\"} 7\n"
+        );
+        assert_eq!(line.lines().count(), 1);
+        assert!(matches!(prometheus_escape("GET"), Cow::Borrowed("GET")));
+    }
+}
@@ -0,0 +1,533 @@
+//! Format specs attached to placeholders (`{{key:spec}}`).
+//!
+//! The spec is everything after the first unescaped `:` of the placeholder (see
+//! [`crate::tag`]). The plain renderers ignore specs; the formatting renderers
+//! format the resolved value accordingly.
+
+use std::fmt;
+
+use rustc_hash::FxHashMap;
+
+use crate::tag::Tag;
+use crate::ZipTemplate;
+
+/// Splits a plain decimal number (`-1234.5`) into sign, integer digits and fraction digits.
+///
+/// Returns `None` for anything that is not an optionally signed run of digits with at
+/// most one `.`, so exponents, `NaN` and friends are left alone.
+fn split_number(value: &str) -> Option<(&str, &str, Option<&str>)> {
+    let (sign, unsigned) = match value.as_bytes().first()? {
+        b'-' | b'+' => value.split_at(1),
+        _ => ("", value),
+    };
+    let (int, frac) = match unsigned.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (unsigned, None),
+    };
+
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if int.is_empty() || !all_digits(int) || !frac.is_none_or(all_digits) {
+        return None;
+    }
+
+    Some((sign, int, frac))
+}
+
+/// Locale settings for grouping numbers rendered through a `{{key:group}}` placeholder.
+///
+/// # Examples
+///
+/// ```
+/// use zip_templates::NumberFormat;
+///
+/// let us = NumberFormat { grouping_char: ',', group_size: 3, decimal_char: '.' };
+/// assert_eq!(us.format("1234567.89").as_deref(), Some("1,234,567.89"));
+/// assert_eq!(us.format("n/a"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Separator inserted between digit groups of the integer part.
+    pub grouping_char: char,
+    /// Number of digits per group. `0` disables grouping.
+    pub group_size: usize,
+    /// Character used in place of the `.` decimal point.
+    pub decimal_char: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            grouping_char: ',',
+            group_size: 3,
+            decimal_char: '.',
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Formats `value` if it is a plain decimal number (`-1234.5`), returning `None` otherwise.
+    pub fn format(&self, value: &str) -> Option<String> {
+        let (sign, int, frac) = split_number(value)?;
+
+        let mut out = String::with_capacity(value.len() + int.len() / self.group_size.max(1));
+        out.push_str(sign);
+        for (i, digit) in int.chars().enumerate() {
+            let remaining = int.len() - i;
+            if i > 0 && self.group_size > 0 && remaining % self.group_size == 0 {
+                out.push(self.grouping_char);
+            }
+            out.push(digit);
+        }
+        if let Some(frac) = frac {
+            out.push(self.decimal_char);
+            out.push_str(frac);
+        }
+
+        Some(out)
+    }
+}
+
+/// Why a format spec could not be applied, see [`FormatError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatErrorKind {
+    /// The spec does not follow the `[[fill]align][+][width][.precision]` grammar.
+    InvalidSpec,
+    /// The spec requires a number but the value is not one.
+    NotANumber(String),
+}
+
+/// A format spec that could not be applied by [`ZipTemplate::render_formatted`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    /// The placeholder key, without its spec.
+    pub key: String,
+    /// The offending spec.
+    pub spec: String,
+    /// What went wrong.
+    pub kind: FormatErrorKind,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            FormatErrorKind::InvalidSpec => {
+                write!(f, "invalid format spec `{}` on `{}`", self.spec, self.key)
+            }
+            FormatErrorKind::NotANumber(value) => write!(
+                f,
+                "format spec `{}` on `{}` needs a number, got `{}`",
+                self.spec, self.key, value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// Largest width or precision a format spec may ask for.
+///
+/// [`ZipTemplate::render_formatted`] treats larger values as an invalid spec, and
+/// [`ZipTemplate::try_parse`] rejects them up front, so a template cannot make every
+/// render allocate an arbitrarily large buffer.
+pub const MAX_SPEC_WIDTH: usize = 4096;
+
+/// Returns `true` if any number in `spec` is above [`MAX_SPEC_WIDTH`].
+///
+/// A fill character is a single char, so every run of digits is either a width or a
+/// precision, or too short to matter.
+pub(crate) fn is_oversized(spec: &str) -> bool {
+    spec.split(|c: char| !c.is_ascii_digit())
+        .filter(|run| !run.is_empty())
+        .any(|run| run.parse::<usize>().map_or(true, |n| n > MAX_SPEC_WIDTH))
+}
+
+/// A parsed `[[fill]align][+][width][.precision]` spec, a subset of Rust's `format!` specs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Spec {
+    fill: char,
+    align: Option<Align>,
+    plus: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl Spec {
+    fn parse(spec: &str) -> Option<Spec> {
+        let align_of = |c| match c {
+            '<' => Some(Align::Left),
+            '^' => Some(Align::Center),
+            '>' => Some(Align::Right),
+            _ => None,
+        };
+
+        let mut rest = spec;
+        let mut parsed = Spec {
+            fill: ' ',
+            align: None,
+            plus: false,
+            width: 0,
+            precision: None,
+        };
+
+        let mut chars = rest.chars();
+        if let (Some(fill), Some(align)) = (chars.next(), chars.next().and_then(align_of)) {
+            parsed.fill = fill;
+            parsed.align = Some(align);
+            rest = chars.as_str();
+        } else if let Some(align) = rest.chars().next().and_then(align_of) {
+            parsed.align = Some(align);
+            rest = &rest[1..];
+        }
+
+        if let Some(unsigned) = rest.strip_prefix('+') {
+            parsed.plus = true;
+            rest = unsigned;
+        }
+
+        let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let width_len = digits(rest);
+        if width_len > 0 {
+            parsed.width = rest[..width_len]
+                .parse()
+                .ok()
+                .filter(|&width| width <= MAX_SPEC_WIDTH)?;
+            rest = &rest[width_len..];
+        }
+
+        if let Some(precision) = rest.strip_prefix('.') {
+            if precision.is_empty() || digits(precision) != precision.len() {
+                return None;
+            }
+            parsed.precision = Some(
+                precision
+                    .parse()
+                    .ok()
+                    .filter(|&precision| precision <= MAX_SPEC_WIDTH)?,
+            );
+            rest = "";
+        }
+
+        rest.is_empty().then_some(parsed)
+    }
+
+    /// Formats `value`, or returns `None` if the spec needs a number and `value` is not one.
+    fn apply(&self, value: &str) -> Option<String> {
+        let (body, numeric) = match (self.precision, self.plus) {
+            (Some(precision), plus) => {
+                let number = value.trim().parse::<f64>().ok().filter(|n| n.is_finite())?;
+                match plus {
+                    true => (format!("{:+.*}", precision, number), true),
+                    false => (format!("{:.*}", precision, number), true),
+                }
+            }
+            // Keep the value's own digits; only make sure a sign is shown.
+            (None, true) => match split_number(value.trim())? {
+                ("", _, _) => (format!("+{}", value.trim()), true),
+                _ => (value.trim().to_string(), true),
+            },
+            (None, false) => (value.to_string(), false),
+        };
+
+        let len = body.chars().count();
+        if len >= self.width {
+            return Some(body);
+        }
+
+        let padding = self.width - len;
+        let align = self
+            .align
+            .unwrap_or(if numeric { Align::Right } else { Align::Left });
+        let (before, after) = match align {
+            Align::Left => (0, padding),
+            Align::Right => (padding, 0),
+            Align::Center => (padding / 2, padding - padding / 2),
+        };
+
+        let mut out = String::with_capacity(body.len() + padding * self.fill.len_utf8());
+        out.extend(std::iter::repeat_n(self.fill, before));
+        out.push_str(&body);
+        out.extend(std::iter::repeat_n(self.fill, after));
+        Some(out)
+    }
+}
+
+impl ZipTemplate {
+    /// Renders a template, applying each placeholder's format spec to its value.
+    ///
+    /// Specs follow a subset of Rust's `format!` syntax:
+    /// `[[fill]align][+][width][.precision]`, where `align` is `<`, `^` or `>`, a
+    /// precision formats the value as a number with that many decimals and `+`
+    /// requires a number and always shows its sign (`{{price:.2}}`, `{{name:<10}}`,
+    /// `{{total:*>8.1}}`, `{{change:+.2}}`). The `group` spec uses
+    /// [`NumberFormat::default`]. Placeholders without a spec render as in
+    /// [`ZipTemplate::render`].
+    ///
+    /// # Errors
+    ///
+    /// Returns every [`FormatError`] encountered, so all problems in a template
+    /// are reported at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("[{{item:<6}}] {{price:>8.2}}");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("item".to_string(), "tea".to_string());
+    /// values.insert("price".to_string(), "3.5".to_string());
+    ///
+    /// assert_eq!(template.render_formatted(&values).unwrap(), "[tea   ]     3.50");
+    /// ```
+    pub fn render_formatted(
+        &self,
+        flat: &FxHashMap<String, String>,
+    ) -> Result<String, Vec<FormatError>> {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let mut errors = Vec::new();
+
+        let resolver = self.resolver(flat);
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+
+            let value = resolver.get(placeholder);
+            let tag = Tag::parse(placeholder);
+            let Some(spec) = tag.spec else {
+                out.push_str(&value);
+                continue;
+            };
+
+            let error = |kind| FormatError {
+                key: tag.key.to_string(),
+                spec: spec.to_string(),
+                kind,
+            };
+            if spec == "group" {
+                match NumberFormat::default().format(&value) {
+                    Some(grouped) => out.push_str(&grouped),
+                    None => errors.push(error(FormatErrorKind::NotANumber(value.into_owned()))),
+                }
+                continue;
+            }
+            match Spec::parse(spec).map(|parsed| parsed.apply(&value)) {
+                Some(Some(formatted)) => out.push_str(&formatted),
+                Some(None) => errors.push(error(FormatErrorKind::NotANumber(value.into_owned()))),
+                None => errors.push(error(FormatErrorKind::InvalidSpec)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(out)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Renders a template, grouping numeric values of `{{key:group}}` placeholders
+    /// according to `format`.
+    ///
+    /// Placeholders without a spec resolve exactly like [`ZipTemplate::render`].
+    /// Values of `group` placeholders that are not plain decimal numbers, and
+    /// placeholders with other specs, are emitted unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::{NumberFormat, ZipTemplate};
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Total: {{amount:group}}");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("amount".to_string(), "1234567.89".to_string());
+    ///
+    /// let rendered = template.render_with_number_format(&values, &NumberFormat::default());
+    /// assert_eq!(rendered, "Total: 1,234,567.89");
+    /// ```
+    pub fn render_with_number_format(
+        &self,
+        flat: &FxHashMap<String, String>,
+        format: &NumberFormat,
+    ) -> String {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        let resolver = self.resolver(flat);
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+
+            let value = resolver.get(placeholder);
+            match Tag::parse(placeholder).spec {
+                Some("group") => match format.format(&value) {
+                    Some(grouped) => out.push_str(&grouped),
+                    None => out.push_str(&value),
+                },
+                _ => out.push_str(&value),
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const US: NumberFormat = NumberFormat {
+        grouping_char: ',',
+        group_size: 3,
+        decimal_char: '.',
+    };
+    const EUROPEAN: NumberFormat = NumberFormat {
+        grouping_char: '.',
+        group_size: 3,
+        decimal_char: ',',
+    };
+
+    #[test]
+    fn number_format_grouping() {
+        assert_eq!(US.format("1234567.89").as_deref(), Some("1,234,567.89"));
+        assert_eq!(
+            EUROPEAN.format("1234567.89").as_deref(),
+            Some("1.234.567,89")
+        );
+        assert_eq!(US.format("-1000").as_deref(), Some("-1,000"));
+        assert_eq!(US.format("999").as_deref(), Some("999"));
+        assert_eq!(US.format("1e9"), None);
+        assert_eq!(US.format(""), None);
+    }
+
+    #[test]
+    fn render_group_spec_us_and_european() {
+        let parsed = ZipTemplate::parse("{{label}}: {{amount:group}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("label".to_string(), "Total".to_string());
+        flat.insert("amount".to_string(), "1234567.89".to_string());
+
+        assert_eq!(
+            parsed.render_with_number_format(&flat, &US),
+            "Total: 1,234,567.89"
+        );
+        assert_eq!(
+            parsed.render_with_number_format(&flat, &EUROPEAN),
+            "Total: 1.234.567,89"
+        );
+    }
+
+    #[test]
+    fn render_group_spec_non_numeric_passthrough() {
+        let parsed = ZipTemplate::parse("{{amount:group}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("amount".to_string(), "unknown".to_string());
+        assert_eq!(parsed.render_with_number_format(&flat, &US), "unknown");
+    }
+
+    #[test]
+    fn spec_grammar() {
+        let spec = Spec::parse("*^10.2").unwrap();
+        assert_eq!(spec.fill, '*');
+        assert_eq!(spec.align, Some(Align::Center));
+        assert_eq!(spec.width, 10);
+        assert_eq!(spec.precision, Some(2));
+
+        assert_eq!(Spec::parse("<5").unwrap().align, Some(Align::Left));
+        assert_eq!(Spec::parse(".0").unwrap().precision, Some(0));
+        assert_eq!(
+            Spec::parse(""),
+            Spec::parse("<0").map(|s| Spec { align: None, ..s })
+        );
+        assert!(Spec::parse(".").is_none());
+        assert!(Spec::parse("10x").is_none());
+        assert!(Spec::parse("bogus").is_none());
+    }
+
+    #[test]
+    fn render_formatted_numeric_spec() {
+        let parsed = ZipTemplate::parse("{{price:.2}}|{{qty:>4}}|{{name:-^9}}|{{total:group}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("price".to_string(), "12.345".to_string());
+        flat.insert("qty".to_string(), "7".to_string());
+        flat.insert("name".to_string(), "tea".to_string());
+        flat.insert("total".to_string(), "1234567".to_string());
+        assert_eq!(
+            parsed.render_formatted(&flat).unwrap(),
+            "12.35|   7|---tea---|1,234,567"
+        );
+    }
+
+    #[test]
+    fn oversized_specs_are_rejected() {
+        assert_eq!(Spec::parse("4096").unwrap().width, MAX_SPEC_WIDTH);
+        assert_eq!(
+            Spec::parse(".4096").unwrap().precision,
+            Some(MAX_SPEC_WIDTH)
+        );
+        assert_eq!(Spec::parse("4097"), None);
+        assert_eq!(Spec::parse(".4097"), None);
+        assert_eq!(Spec::parse("999999999999999999999999"), None);
+
+        assert!(!is_oversized("*^4096.2"));
+        assert!(!is_oversized("group"));
+        assert!(is_oversized(">999999999999"));
+        assert!(is_oversized("1.4097"));
+
+        let parsed = ZipTemplate::parse("{{x:999999999999}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("x".to_string(), "1".to_string());
+        assert_eq!(
+            parsed.render_formatted(&flat).unwrap_err()[0].kind,
+            FormatErrorKind::InvalidSpec
+        );
+        assert_eq!(parsed.render_with_number_format(&flat, &US), "1");
+    }
+
+    #[test]
+    fn render_formatted_reports_all_errors() {
+        let parsed = ZipTemplate::parse("{{price:.2}} {{name:??}} {{ok}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("price".to_string(), "cheap".to_string());
+        flat.insert("ok".to_string(), "fine".to_string());
+
+        assert_eq!(
+            parsed.render_formatted(&flat).unwrap_err(),
+            [
+                FormatError {
+                    key: "price".to_string(),
+                    spec: ".2".to_string(),
+                    kind: FormatErrorKind::NotANumber("cheap".to_string()),
+                },
+                FormatError {
+                    key: "name".to_string(),
+                    spec: "??".to_string(),
+                    kind: FormatErrorKind::InvalidSpec,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn plus_sign_directive() {
+        let parsed = ZipTemplate::parse("{{change:+}} {{change:+.2}} {{change:>+6}}");
+        let mut flat = FxHashMap::default();
+        let mut render = |value: &str| {
+            flat.insert("change".to_string(), value.to_string());
+            parsed.render_formatted(&flat)
+        };
+
+        assert_eq!(render("5").unwrap(), "+5 +5.00     +5");
+        assert_eq!(render("-3").unwrap(), "-3 -3.00     -3");
+        assert_eq!(render("0").unwrap(), "+0 +0.00     +0");
+        assert_eq!(render("+1.5").unwrap(), "+1.5 +1.50   +1.5");
+        assert_eq!(
+            render("n/a").unwrap_err()[0].kind,
+            FormatErrorKind::NotANumber("n/a".to_string())
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Flattening of non-JSON documents.
+//!
+//! Each helper converts its format's value type into a `serde_json::Value` and
+//! delegates to [`flatten_json`], so the resulting keys follow the same
+//! dot-separated layout regardless of where the data came from.
+
+use rustc_hash::FxHashMap;
+use serde_json::Value;
+
+use crate::flatten_json;
+
+/// Flattens a TOML document into a flat map with dot-separated keys.
+///
+/// Datetimes are rendered in their TOML (RFC 3339) form.
+///
+/// # Examples
+///
+/// ```
+/// let doc: toml::Value = toml::from_str("[user]\nname = \"Alice\"").unwrap();
+/// let flat = zip_templates::flatten_toml(&doc);
+/// assert_eq!(flat.get("user.name"), Some(&"Alice".to_string()));
+/// ```
+#[cfg(feature = "toml")]
+pub fn flatten_toml(value: &toml::Value) -> FxHashMap<String, String> {
+    fn convert(value: &toml::Value) -> Value {
+        match value {
+            toml::Value::String(s) => Value::String(s.clone()),
+            toml::Value::Integer(i) => Value::from(*i),
+            toml::Value::Float(f) => Value::from(*f),
+            toml::Value::Boolean(b) => Value::Bool(*b),
+            toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+            toml::Value::Array(arr) => Value::Array(arr.iter().map(convert).collect()),
+            toml::Value::Table(table) => {
+                Value::Object(table.iter().map(|(k, v)| (k.clone(), convert(v))).collect())
+            }
+        }
+    }
+    flatten_json(&convert(value))
+}
+
+/// Flattens a YAML document into a flat map with dot-separated keys.
+///
+/// Non-string mapping keys are converted to strings (`1`, `true`), and tags are
+/// ignored in favour of the tagged value.
+///
+/// # Examples
+///
+/// ```
+/// let doc: serde_yaml::Value = serde_yaml::from_str("user:\n  name: Alice").unwrap();
+/// let flat = zip_templates::flatten_yaml(&doc);
+/// assert_eq!(flat.get("user.name"), Some(&"Alice".to_string()));
+/// ```
+#[cfg(feature = "yaml")]
+pub fn flatten_yaml(value: &serde_yaml::Value) -> FxHashMap<String, String> {
+    fn key(value: &serde_yaml::Value) -> String {
+        match value {
+            serde_yaml::Value::String(s) => s.clone(),
+            other => flatten_json(&convert(other)).remove("").unwrap_or_default(),
+        }
+    }
+    fn convert(value: &serde_yaml::Value) -> Value {
+        match value {
+            serde_yaml::Value::Null => Value::Null,
+            serde_yaml::Value::Bool(b) => Value::Bool(*b),
+            serde_yaml::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    Value::from(i)
+                } else if let Some(u) = n.as_u64() {
+                    Value::from(u)
+                } else {
+                    n.as_f64().map_or(Value::Null, Value::from)
+                }
+            }
+            serde_yaml::Value::String(s) => Value::String(s.clone()),
+            serde_yaml::Value::Sequence(seq) => Value::Array(seq.iter().map(convert).collect()),
+            serde_yaml::Value::Mapping(map) => {
+                Value::Object(map.iter().map(|(k, v)| (key(k), convert(v))).collect())
+            }
+            serde_yaml::Value::Tagged(tagged) => convert(&tagged.value),
+        }
+    }
+    flatten_json(&convert(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected() -> FxHashMap<String, String> {
+        flatten_json(&serde_json::json!({
+            "user": { "name": "Alice", "age": 30, "tags": ["admin", "ops"] },
+            "active": true,
+            "ratio": 0.5
+        }))
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_matches_json() {
+        let doc: toml::Value = toml::from_str(
+            r#"
+            active = true
+            ratio = 0.5
+
+            [user]
+            name = "Alice"
+            age = 30
+            tags = ["admin", "ops"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(flatten_toml(&doc), expected());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_matches_json() {
+        let doc: serde_yaml::Value = serde_yaml::from_str(
+            "
+active: true
+ratio: 0.5
+user:
+  name: Alice
+  age: 30
+  tags: [admin, ops]
+",
+        )
+        .unwrap();
+        assert_eq!(flatten_yaml(&doc), expected());
+    }
+}
@@ -0,0 +1,127 @@
+//! Deduplication of static fragments across many parsed templates.
+//!
+//! Compiling a directory of similar templates re-allocates the same fragments
+//! (`"\n    <td>"`, closing tags) over and over. Parsing through a shared
+//! [`StaticInterner`] stores each distinct fragment once as an `Arc<str>`.
+
+use std::sync::Arc;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{lookup, ZipTemplate};
+
+/// A set of interned static fragments shared by every template parsed through it.
+#[derive(Debug, Clone, Default)]
+pub struct StaticInterner {
+    fragments: FxHashSet<Arc<str>>,
+}
+
+impl StaticInterner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared allocation for `fragment`, storing it on first use.
+    pub fn intern(&mut self, fragment: &str) -> Arc<str> {
+        if let Some(existing) = self.fragments.get(fragment) {
+            return Arc::clone(existing);
+        }
+        let fragment: Arc<str> = Arc::from(fragment);
+        self.fragments.insert(Arc::clone(&fragment));
+        fragment
+    }
+
+    /// The number of distinct fragments interned so far.
+    pub fn len(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// Returns `true` if nothing has been interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.fragments.is_empty()
+    }
+}
+
+/// A parsed template whose statics are interned, see [`ZipTemplate::parse_with_interner`].
+#[derive(Debug, Clone)]
+pub struct InternedTemplate {
+    /// The static parts of the template, shared with other templates of the same interner.
+    pub statics: Vec<Arc<str>>,
+    /// The placeholder keys to be replaced with dynamic values.
+    pub placeholders: Vec<String>,
+    pre_emptive_size: usize,
+}
+
+impl ZipTemplate {
+    /// Parses a template like [`ZipTemplate::parse`], interning its statics in `interner`.
+    ///
+    /// Identical fragments of all templates parsed through the same interner share
+    /// one allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use zip_templates::{StaticInterner, ZipTemplate};
+    ///
+    /// let mut interner = StaticInterner::new();
+    /// let a = ZipTemplate::parse_with_interner("<td>{{a}}</td>", &mut interner);
+    /// let b = ZipTemplate::parse_with_interner("<td>{{b}}</td>", &mut interner);
+    ///
+    /// assert!(Arc::ptr_eq(&a.statics[0], &b.statics[0]));
+    /// assert_eq!(interner.len(), 2);
+    /// ```
+    pub fn parse_with_interner(template: &str, interner: &mut StaticInterner) -> InternedTemplate {
+        let parsed = ZipTemplate::parse(template);
+        InternedTemplate {
+            statics: parsed.statics.iter().map(|s| interner.intern(s)).collect(),
+            placeholders: parsed.placeholders,
+            pre_emptive_size: parsed.pre_emptive_size,
+        }
+    }
+}
+
+impl InternedTemplate {
+    /// Renders the template like [`ZipTemplate::render`].
+    pub fn render(&self, flat: &FxHashMap<String, String>) -> String {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let dynamics = self
+            .placeholders
+            .iter()
+            .map(|placeholder| lookup(flat, placeholder));
+
+        self.statics.iter().zip(dynamics).for_each(|(s, dynamic)| {
+            out.push_str(s);
+            out.push_str(&dynamic);
+        });
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_share_interned_fragments() {
+        let mut interner = StaticInterner::new();
+        let first =
+            ZipTemplate::parse_with_interner("<tr>\n    <td>{{a}}</td></tr>", &mut interner);
+        let second =
+            ZipTemplate::parse_with_interner("<ul>{{x}}\n    <td>{{b}}</td></tr>", &mut interner);
+
+        // "</td></tr>" is the trailing static of both templates.
+        assert!(Arc::ptr_eq(&first.statics[1], &second.statics[2]));
+        assert!(!Arc::ptr_eq(&first.statics[0], &second.statics[0]));
+        assert_eq!(interner.len(), 4);
+
+        let mut flat = FxHashMap::default();
+        flat.insert("a".to_string(), "1".to_string());
+        flat.insert("x".to_string(), "2".to_string());
+        flat.insert("b".to_string(), "3".to_string());
+        assert_eq!(first.render(&flat), "<tr>\n    <td>1</td></tr>");
+        assert_eq!(second.render(&flat), "<ul>2\n    <td>3</td></tr>");
+    }
+}
@@ -3,9 +3,56 @@
 //! - parse: splits template into `statics` and `placeholders` vectors
 //! - render: resolves placeholder dot-paths against a `serde_json::Value` and zips/stitches the final output
 
-use rustc_hash::FxHashMap;
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde_json::Value;
 
+mod adaptive;
+mod codec;
+#[cfg(feature = "flate2")]
+mod compress;
+mod computed;
+mod config;
+mod escape;
+mod format;
+#[cfg(any(feature = "toml", feature = "yaml"))]
+mod formats;
+mod interner;
+#[cfg(feature = "chrono")]
+mod now;
+mod pooled;
+mod registry;
+mod schema;
+mod shared;
+#[cfg(feature = "smallstr")]
+mod small;
+mod tag;
+
+pub use adaptive::AdaptiveTemplate;
+pub use codec::DecodeError;
+pub use config::TemplateConfig;
+pub use escape::{prometheus_escape, xml_attr_escape, xml_escape};
+pub use format::{FormatError, FormatErrorKind, NumberFormat, MAX_SPEC_WIDTH};
+#[cfg(feature = "toml")]
+pub use formats::flatten_toml;
+#[cfg(feature = "yaml")]
+pub use formats::flatten_yaml;
+pub use interner::{InternedTemplate, StaticInterner};
+#[cfg(feature = "chrono")]
+pub use now::NowFormatError;
+pub use pooled::{PoolError, PooledTemplate};
+pub use registry::{IncludeError, TemplateRegistry};
+pub use schema::{validate_against_schema, SchemaIssue};
+pub use shared::SharedValues;
+
+/// The placeholder delimiters recognized by [`ZipTemplate::parse`].
+const DELIMITERS: &[(&str, &str)] = &[("{{", "}}")];
+
 /// Represents a parsed ZipTemplate, containing static and dynamic parts.
 ///
 /// A `ZipTemplate` is created from a template string. The parsing process
@@ -18,11 +65,298 @@ pub struct ZipTemplate {
     /// The placeholder keys to be replaced with dynamic values.
     pub placeholders: Vec<String>,
     pre_emptive_size: usize,
+    computed: computed::Computed,
+}
+
+/// How to render a template against a JSON document, as recommended by
+/// [`ZipTemplate::recommend_strategy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderStrategy {
+    /// Flatten the whole document with [`flatten_json`] and [`ZipTemplate::render`].
+    FlattenAll,
+    /// Keep only the referenced keys while flattening with [`flatten_json_visit`],
+    /// then [`ZipTemplate::render`].
+    PartialFlatten,
+    /// Resolve the paths in the document with [`ZipTemplate::render_json`].
+    DirectPaths,
+}
+
+/// What [`ZipTemplate::render_json_with`] renders for a placeholder whose path
+/// leads to an object or an array rather than a scalar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ObjectPolicy {
+    /// Render nothing, as [`ZipTemplate::render`] does against [`flatten_json`].
+    #[default]
+    Empty,
+    /// Render the compact JSON serialization, as a `{{*path}}` placeholder does.
+    Json,
+    /// Render the first scalar reached by descending through first fields and
+    /// first items. Fields come in key order, unless serde_json's `preserve_order`
+    /// feature is enabled, so `{"name": "Alice", "age": 30}` renders `30`.
+    FirstField,
+    /// Fail with a [`NonScalarError`].
+    Error,
+}
+
+/// Provenance of a rendered segment, as returned by [`ZipTemplate::render_annotated`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Text copied verbatim from the template.
+    Static,
+    /// Text substituted for the placeholder `key`.
+    Dynamic { key: String },
+}
+
+/// A piece of a template, used to build a [`ZipTemplate`] with [`ZipTemplate::from_tokens`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// Literal template text.
+    Static(String),
+    /// A placeholder key.
+    Placeholder(String),
+}
+
+/// Resolves the rest of a key after its namespace prefix, see
+/// [`ZipTemplate::render_with_providers`].
+pub type ValueProvider<'a> = dyn Fn(&str) -> Option<String> + 'a;
+
+/// Receives the segments of a render in order, see [`ZipTemplate::render_events`].
+pub trait SegmentHandler {
+    /// Called with a run of static template text.
+    fn on_static(&mut self, text: &str);
+    /// Called with the value substituted for the placeholder `key`.
+    fn on_value(&mut self, key: &str, value: &str);
+}
+
+/// Hooks called around each placeholder substitution, see
+/// [`ZipTemplate::render_instrumented`].
+pub trait RenderHooks {
+    /// Called before the placeholder `key` is resolved.
+    fn before_placeholder(&mut self, key: &str);
+    /// Called once `key` has been resolved to `value` and written to the output.
+    fn after_placeholder(&mut self, key: &str, value: &str);
+}
+
+/// A source of values of any [`fmt::Display`] type, see [`ZipTemplate::render_dyn`].
+pub trait DynContext {
+    /// Returns the value for the placeholder `key`, or `None` if it has none.
+    fn get(&self, key: &str) -> Option<Box<dyn fmt::Display>>;
+}
+
+/// Error returned when a segment index is outside the template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexError {
+    /// The requested index.
+    pub index: usize,
+    /// The number of segments available.
+    pub len: usize,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "segment index {} out of range for template with {} segments",
+            self.index, self.len
+        )
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+/// Figures computed by [`ZipTemplate::dry_run`] without rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRun<'a> {
+    /// The length in bytes the rendered output would have.
+    pub output_size: usize,
+    /// The distinct placeholders that would not resolve, in order of first appearance.
+    pub missing_keys: Vec<&'a str>,
+    /// The number of placeholder occurrences that would resolve.
+    pub resolved_keys: usize,
+}
+
+/// Error returned by [`ZipTemplate::render_rows`] when columns have different lengths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnLengthError {
+    /// The column whose length disagrees with the first column.
+    pub key: String,
+    /// Its length.
+    pub len: usize,
+    /// The length of the first column referenced by the template.
+    pub expected: usize,
+}
+
+impl fmt::Display for ColumnLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "column `{}` has {} rows, expected {}",
+            self.key, self.len, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ColumnLengthError {}
+
+/// Error returned by [`ZipTemplate::render_from_vec_ordered`] when the values and
+/// the key order have different lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderLengthError {
+    /// The number of values supplied.
+    pub values: usize,
+    /// The number of keys in the order.
+    pub keys: usize,
+}
+
+impl fmt::Display for OrderLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} values supplied for {} keys", self.values, self.keys)
+    }
+}
+
+impl std::error::Error for OrderLengthError {}
+
+/// Error returned by the strict parsers such as [`ZipTemplate::try_parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A placeholder whose key is empty or only `.` separators. `span` covers the
+    /// whole tag, delimiters included.
+    DegeneratePlaceholder { span: Range<usize> },
+    /// The template is `len` bytes long, more than the `max` accepted.
+    TooLong { len: usize, max: usize },
+    /// A `|>` filter that is not `trim`, `upper` or `lower`. `span` covers the
+    /// whole tag, delimiters included.
+    UnknownFilter { name: String, span: Range<usize> },
+    /// A format spec whose width or precision is above [`MAX_SPEC_WIDTH`]. `span`
+    /// covers the whole tag, delimiters included.
+    SpecTooLarge { spec: String, span: Range<usize> },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::DegeneratePlaceholder { span } => write!(
+                f,
+                "placeholder at {}..{} has no usable key",
+                span.start, span.end
+            ),
+            ParseError::TooLong { len, max } => {
+                write!(f, "template is {} bytes long, limit is {}", len, max)
+            }
+            ParseError::UnknownFilter { name, span } => write!(
+                f,
+                "placeholder at {}..{} uses the unknown filter `{}`",
+                span.start, span.end, name
+            ),
+            ParseError::SpecTooLarge { spec, span } => write!(
+                f,
+                "placeholder at {}..{} has the format spec `{}`, wider than {}",
+                span.start, span.end, spec, MAX_SPEC_WIDTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Error returned by [`ZipTemplate::parse_with_limits`] when a template exceeds a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitError {
+    /// The placeholder at `index` has a key of `len` bytes, more than `max`.
+    KeyTooLong {
+        index: usize,
+        len: usize,
+        max: usize,
+    },
+    /// The template contains more than `max` placeholders.
+    TooManyPlaceholders { max: usize },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::KeyTooLong { index, len, max } => write!(
+                f,
+                "placeholder {} has a {} byte key, limit is {}",
+                index, len, max
+            ),
+            LimitError::TooManyPlaceholders { max } => {
+                write!(f, "template has more than {} placeholders", max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+/// Error returned by [`ZipTemplate::render_flat`] for a template containing a
+/// control-flow tag such as `{{#each items}}` or `{{/each}}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlTagError {
+    /// Index of the first control tag among the placeholders.
+    pub index: usize,
+    /// Its trimmed content, e.g. `#each items`.
+    pub tag: String,
+}
+
+impl fmt::Display for ControlTagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "placeholder {} is the control tag `{}`, which flat rendering does not support",
+            self.index, self.tag
+        )
+    }
+}
+
+impl std::error::Error for ControlTagError {}
+
+/// Error returned by [`ZipTemplate::render_json_with`] under [`ObjectPolicy::Error`]
+/// when a placeholder leads to an object or an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonScalarError {
+    /// Index of the placeholder among the placeholders.
+    pub index: usize,
+    /// The key that leads to the non-scalar value.
+    pub key: String,
+}
+
+impl fmt::Display for NonScalarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "placeholder {} key `{}` points at an object or array, not a scalar",
+            self.index, self.key
+        )
+    }
+}
+
+impl std::error::Error for NonScalarError {}
+
+/// Formats the template back to source text, writing each placeholder as
+/// `{{key}}`. See [`ZipTemplate::is_roundtrip_stable`] for when parsing the result
+/// gives back the same template.
+impl fmt::Display for ZipTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (s, placeholder) in self.statics.iter().zip(self.real_placeholders().iter()) {
+            write!(f, "{}{{{{{}}}}}", s, placeholder)?;
+        }
+        match self.statics.last() {
+            Some(last) if self.statics.len() > self.real_placeholders().len() => f.write_str(last),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl ZipTemplate {
     /// Parse a template into `statics` and `placeholders`.
     /// Placeholder syntax: `{{path.to.value}}` (trimmed).
+    /// A placeholder may list fallback keys, `{{primary ?? secondary}}`: the first
+    /// key with a non-empty value in the data map is used. A literal default can
+    /// follow a `|` (`{{name | anonymous}}`), and a format spec a `:`
+    /// (`{{amount:group}}`); write `\|` and `\:` for literal pipes and colons.
+    /// Include tags (`{{> partial}}`) are kept as placeholders and resolved by
+    /// [`ZipTemplate::render_with_registry`].
     ///
     /// # Examples
     ///
@@ -30,6 +364,92 @@ impl ZipTemplate {
         ZipTemplate::parse_with_capacity(template, (template.len() as f32 * 1.5) as usize)
     }
 
+    /// Parses a template like [`ZipTemplate::parse`], rejecting degenerate placeholders.
+    ///
+    /// A placeholder is degenerate when its key is empty or made only of `.`
+    /// separators after trimming (`{{ }}`, `{{.}}`, `{{..}}`). Such keys are almost
+    /// always authoring mistakes, and an empty key is indistinguishable from the
+    /// alignment slot. [`ZipTemplate::parse`] keeps them as-is.
+    ///
+    /// `|>` filters other than `trim`, `upper` and `lower` are rejected as well;
+    /// [`ZipTemplate::parse`] keeps them and renders the value unfiltered. So are
+    /// format specs with a width or precision above [`MAX_SPEC_WIDTH`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError::DegeneratePlaceholder`], [`ParseError::UnknownFilter`] or
+    /// [`ParseError::SpecTooLarge`] with the byte span of the first offending tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::{ParseError, ZipTemplate};
+    ///
+    /// assert!(ZipTemplate::try_parse("Hello {{name}}!").is_ok());
+    /// assert_eq!(
+    ///     ZipTemplate::try_parse("Hello {{ }}!").unwrap_err(),
+    ///     ParseError::DegeneratePlaceholder { span: 6..11 }
+    /// );
+    /// ```
+    pub fn try_parse(template: &str) -> Result<Self, ParseError> {
+        ZipTemplate::scan(
+            template,
+            DELIMITERS,
+            false,
+            (template.len() as f32 * 1.5) as usize,
+            |key, _, span| {
+                if key.chars().all(|c| c == '.') {
+                    return Err(ParseError::DegeneratePlaceholder { span });
+                }
+                let tag = tag::Tag::parse(key);
+                if let Some(name) = tag.filters().find(|name| !tag::is_filter(name)) {
+                    return Err(ParseError::UnknownFilter {
+                        name: name.to_string(),
+                        span,
+                    });
+                }
+                if let Some(spec) = tag.spec.filter(|spec| format::is_oversized(spec)) {
+                    return Err(ParseError::SpecTooLarge {
+                        spec: spec.to_string(),
+                        span,
+                    });
+                }
+                Ok(true)
+            },
+        )
+    }
+
+    /// Parses a template like [`ZipTemplate::parse`], rejecting templates longer than
+    /// `max_len` bytes before scanning them.
+    ///
+    /// This is a cheap guard for user-submitted templates; see
+    /// [`ZipTemplate::parse_with_limits`] for limits on the placeholders themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError::TooLong`] if `template.len() > max_len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::{ParseError, ZipTemplate};
+    ///
+    /// assert!(ZipTemplate::parse_bounded("Hi {{name}}", 16).is_ok());
+    /// assert_eq!(
+    ///     ZipTemplate::parse_bounded("Hi {{name}}", 8).unwrap_err(),
+    ///     ParseError::TooLong { len: 11, max: 8 }
+    /// );
+    /// ```
+    pub fn parse_bounded(template: &str, max_len: usize) -> Result<Self, ParseError> {
+        if template.len() > max_len {
+            return Err(ParseError::TooLong {
+                len: template.len(),
+                max: max_len,
+            });
+        }
+        Ok(ZipTemplate::parse(template))
+    }
+
     /// Creates a new `ZipTemplate` by parsing the provided string and setting a custom
     /// initial buffer capacity.
     ///
@@ -61,36 +481,216 @@ impl ZipTemplate {
     ///
     /// ```
     pub fn parse_with_capacity(template: &str, pre_emptive_size: usize) -> Self {
+        let parsed: Result<Self, std::convert::Infallible> =
+            ZipTemplate::scan(template, DELIMITERS, false, pre_emptive_size, |_, _, _| {
+                Ok(true)
+            });
+        match parsed {
+            Ok(parsed) => parsed,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Parses a template like [`ZipTemplate::parse`], enforcing limits suited to
+    /// untrusted input.
+    ///
+    /// The limits are checked while scanning, so an oversized template is rejected
+    /// as soon as the first offending placeholder is seen.
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The input string containing text and `{{ placeholder }}` tags.
+    /// * `max_key_len` - The maximum length (in bytes) of a trimmed placeholder key.
+    /// * `max_placeholders` - The maximum number of placeholders in the template.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LimitError::KeyTooLong`] or [`LimitError::TooManyPlaceholders`] for the
+    /// first limit exceeded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::{LimitError, ZipTemplate};
+    ///
+    /// assert!(ZipTemplate::parse_with_limits("Hi {{name}}", 8, 4).is_ok());
+    /// assert_eq!(
+    ///     ZipTemplate::parse_with_limits("{{a}}{{b}}", 8, 1).unwrap_err(),
+    ///     LimitError::TooManyPlaceholders { max: 1 }
+    /// );
+    /// ```
+    pub fn parse_with_limits(
+        template: &str,
+        max_key_len: usize,
+        max_placeholders: usize,
+    ) -> Result<Self, LimitError> {
+        ZipTemplate::scan(
+            template,
+            DELIMITERS,
+            false,
+            (template.len() as f32 * 1.5) as usize,
+            |key, index, _| {
+                if index >= max_placeholders {
+                    return Err(LimitError::TooManyPlaceholders {
+                        max: max_placeholders,
+                    });
+                }
+                if key.len() > max_key_len {
+                    return Err(LimitError::KeyTooLong {
+                        index,
+                        len: key.len(),
+                        max: max_key_len,
+                    });
+                }
+                Ok(true)
+            },
+        )
+    }
+
+    /// Parses a template recognizing both `{{key}}` and shell-style `${KEY}` placeholders.
+    ///
+    /// Both forms produce ordinary placeholders with trimmed keys, so a template
+    /// being migrated from `${VAR}` syntax can mix the two and render from a single
+    /// map. A tag is closed by the delimiter matching its opener.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse_multi_syntax("${HOME}/{{user.name}}");
+    /// assert_eq!(template.statics, ["", "/", ""]);
+    /// assert_eq!(template.placeholders, ["HOME", "user.name", ""]);
+    /// ```
+    pub fn parse_multi_syntax(template: &str) -> Self {
+        let parsed: Result<Self, std::convert::Infallible> = ZipTemplate::scan(
+            template,
+            &[("{{", "}}"), ("${", "}")],
+            false,
+            (template.len() as f32 * 1.5) as usize,
+            |_, _, _| Ok(true),
+        );
+        match parsed {
+            Ok(parsed) => parsed,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Parses a template like [`ZipTemplate::parse`], keeping tags of kinds this
+    /// version may not understand as literal static text.
+    ///
+    /// A tag whose trimmed content starts with one of the sigils `#`, `/`, `>`, `!` or
+    /// `@` (sections, includes, comments, built-ins) is left verbatim in the
+    /// surrounding static instead of becoming a value placeholder, so a template
+    /// written for a newer tag set still parses and renders its unknown tags as-is.
+    /// This includes `{{> partial}}` and `{{@now}}`; parse with
+    /// [`ZipTemplate::parse`] to resolve those.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse_lenient_tags("{{#each items}}{{name}}{{/each}}");
+    /// assert_eq!(template.statics, ["{{#each items}}", "{{/each}}"]);
+    /// assert_eq!(template.placeholders, ["name", ""]);
+    /// ```
+    pub fn parse_lenient_tags(template: &str) -> Self {
+        let parsed: Result<Self, std::convert::Infallible> = ZipTemplate::scan(
+            template,
+            DELIMITERS,
+            false,
+            (template.len() as f32 * 1.5) as usize,
+            |key, _, _| Ok(!key.starts_with(['#', '/', '>', '!', '@'])),
+        );
+        match parsed {
+            Ok(parsed) => parsed,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Splits `template` into statics and placeholders, calling `check` with each
+    /// trimmed placeholder key, its index and the byte span of the whole tag before
+    /// it is stored. Scanning stops at the first error returned by `check`; when it
+    /// returns `Ok(false)` the tag is kept verbatim as part of the surrounding static.
+    ///
+    /// `delimiters` lists the `(open, close)` pairs that delimit a placeholder; the
+    /// earliest opener wins and is closed by its own closing delimiter. With
+    /// `brace_escapes`, an opener preceded by `\` is not a tag, and `\{{` / `\}}`
+    /// are unescaped to `{{` / `}}` in the statics.
+    fn scan<E>(
+        template: &str,
+        delimiters: &[(&str, &str)],
+        brace_escapes: bool,
+        pre_emptive_size: usize,
+        mut check: impl FnMut(&str, usize, Range<usize>) -> Result<bool, E>,
+    ) -> Result<Self, E> {
         let mut statics = Vec::new();
         let mut placeholders = Vec::new();
         let mut cursor = 0;
+        let literal = |text: &str| match brace_escapes && text.contains('\\') {
+            true => text.replace("\\{{", "{{").replace("\\}}", "}}"),
+            false => text.to_string(),
+        };
+
+        // Next position of each opener, refreshed only once the cursor passes it so
+        // that a rare opener is not searched for again on every tag.
+        let mut next_open: Vec<Option<usize>> = delimiters
+            .iter()
+            .map(|(open, _)| template.find(open))
+            .collect();
+
+        while let Some((pair, open_idx)) = next_open
+            .iter()
+            .enumerate()
+            .filter_map(|(pair, pos)| pos.map(|pos| (pair, pos)))
+            .min_by_key(|&(_, pos)| pos)
+        {
+            let (open, close) = delimiters[pair];
+            let content_start = open_idx + open.len();
 
-        while let Some(start_offset) = template[cursor..].find("{{") {
-            let open_idx = cursor + start_offset;
+            if brace_escapes && template[..open_idx].ends_with('\\') {
+                next_open[pair] = template[content_start..]
+                    .find(open)
+                    .map(|offset| content_start + offset);
+                continue;
+            }
 
             // Search for closing tags strictly after the opening tags
             // Equivalent to the non-greedy regex `.*?` behavior
-            if let Some(end_offset) = template[open_idx + 2..].find("}}") {
-                let close_idx = open_idx + 2 + end_offset;
-
-                // Push the text before the placeholder as a static segment
-                statics.push(template[cursor..open_idx].to_string());
+            if let Some(end_offset) = template[content_start..].find(close) {
+                let close_idx = content_start + end_offset;
 
                 // Extract and trim the placeholder content
-                let content = &template[open_idx + 2..close_idx];
-                placeholders.push(content.trim().to_string());
+                let content = template[content_start..close_idx].trim();
+                // The search for the next tag resumes here even when the tag is kept
+                // as static text and `cursor` stays behind it.
+                let resume = close_idx + close.len();
+                let keep = check(content, placeholders.len(), open_idx..resume)?;
+
+                if keep {
+                    // Push the text before the placeholder as a static segment
+                    statics.push(literal(&template[cursor..open_idx]));
+                    placeholders.push(content.to_string());
 
-                // Advance cursor past the closing tags
-                cursor = close_idx + 2;
+                    // Advance cursor past the closing tags
+                    cursor = resume;
+                }
+
+                for ((open, _), pos) in delimiters.iter().zip(next_open.iter_mut()) {
+                    if pos.is_some_and(|pos| pos < resume) {
+                        *pos = template[resume..].find(open).map(|offset| resume + offset);
+                    }
+                }
             } else {
-                // If no closing "}}" is found, stop parsing placeholders
+                // If no closing delimiter is found, stop parsing placeholders
                 // and treat the rest as static text.
                 break;
             }
         }
 
         // Push the remainder of the string
-        statics.push(template[cursor..].to_string());
+        statics.push(literal(&template[cursor..]));
 
         // Ensure alignment for the zip iterator (Static -> Dynamic -> Static...)
         // The zip logic requires placeholders to match statics count or handle the offset.
@@ -99,10 +699,61 @@ impl ZipTemplate {
             placeholders.push(String::new());
         }
 
-        ZipTemplate {
+        Ok(ZipTemplate {
             statics,
             placeholders,
             pre_emptive_size,
+            computed: Default::default(),
+        })
+    }
+
+    /// Builds a template from a sequence of tokens instead of source text.
+    ///
+    /// This lets converters from other template formats produce a `ZipTemplate`
+    /// directly. Any token sequence is accepted: consecutive statics are merged, an
+    /// empty static is inserted between consecutive placeholders, and the usual
+    /// alignment slot is added, so the result has the same shape [`ZipTemplate::parse`]
+    /// would produce. Placeholder keys are taken as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::{Token, ZipTemplate};
+    ///
+    /// let template = ZipTemplate::from_tokens(
+    ///     [
+    ///         Token::Static("Hello ".to_string()),
+    ///         Token::Placeholder("name".to_string()),
+    ///         Token::Static("!".to_string()),
+    ///     ]
+    ///     .into_iter(),
+    /// );
+    /// assert_eq!(template.statics, ["Hello ", "!"]);
+    /// assert_eq!(template.placeholders, ["name", ""]);
+    /// ```
+    pub fn from_tokens<I: Iterator<Item = Token>>(tokens: I) -> ZipTemplate {
+        let mut statics = Vec::new();
+        let mut placeholders = Vec::new();
+        let mut current = String::new();
+
+        for token in tokens {
+            match token {
+                Token::Static(text) => current.push_str(&text),
+                Token::Placeholder(key) => {
+                    statics.push(std::mem::take(&mut current));
+                    placeholders.push(key);
+                }
+            }
+        }
+        statics.push(current);
+        placeholders.push(String::new());
+
+        let static_len: usize = statics.iter().map(String::len).sum();
+        ZipTemplate {
+            statics,
+            placeholders,
+            pre_emptive_size: (static_len as f32 * 1.5) as usize,
+            computed: Default::default(),
         }
     }
 
@@ -111,281 +762,4092 @@ impl ZipTemplate {
         self.statics.len()
     }
 
-    /// Renders a template by resolving placeholders against a provided map of values.
+    /// Replaces the static segment at `index` with `new_text`, without re-parsing.
     ///
-    /// This function efficiently assembles a final string by interleaving the static parts
-    /// of the template with dynamic values looked up from the provided `flat` map.
+    /// `new_text` is taken literally: any `{{ }}` in it stays static text.
     ///
-    /// # Allocation Strategy
+    /// # Errors
     ///
-    /// The output buffer is pre-allocated using `self.pre_emptive_size` to minimize reallocations.
+    /// Returns an [`IndexError`] if `index` is not less than [`ZipTemplate::static_parts_count`].
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    /// * `flat` - A map containing placeholder keys and their replacement values. If a key is
-    ///   missing in the map, it defaults to an empty string.
+    /// ```
+    /// use zip_templates::ZipTemplate;
     ///
-    /// # Returns
+    /// let mut template = ZipTemplate::parse("{{a}} and {{b}}");
+    /// template.replace_static(1, " or ").unwrap();
+    /// assert_eq!(template.statics, ["", " or ", ""]);
+    /// assert!(template.replace_static(3, "").is_err());
+    /// ```
+    pub fn replace_static(&mut self, index: usize, new_text: &str) -> Result<(), IndexError> {
+        let len = self.statics.len();
+        let segment = self
+            .statics
+            .get_mut(index)
+            .ok_or(IndexError { index, len })?;
+        segment.clear();
+        segment.push_str(new_text);
+        Ok(())
+    }
+
+    /// Updates the template after `edited_range` of its source `original` was
+    /// replaced by `new_text`, re-scanning only the segments the edit touches.
     ///
-    /// A new `String` containing the fully rendered content.
+    /// The result is the same as parsing the edited source with
+    /// [`ZipTemplate::parse`], but only the statics and placeholders between the
+    /// last tag ending before the edit and the first tag starting after it are
+    /// rebuilt. If the rebuilt region ends with an opener that could pair with a
+    /// later tag's delimiters (an edit that starts a tag), the region is widened
+    /// until the tags outside it are unaffected. The capacity hint grows or shrinks
+    /// with the source.
+    ///
+    /// `original` must be the `{{ }}` source this template was parsed from; if it
+    /// does not match, the edited source is parsed in full instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `edited_range` is out of bounds or not on char boundaries of
+    /// `original`.
     ///
     /// # Examples
     ///
     /// ```
     /// use zip_templates::ZipTemplate;
-    /// use rustc_hash::FxHashMap;
-    ///
-    /// let template = ZipTemplate::parse("Hello, {{name}}!");
-    /// let mut values = FxHashMap::default();
-    /// values.insert("name".to_string(), "World".to_string());
     ///
-    /// let rendered = template.render(&values);
-    /// assert_eq!(rendered, "Hello, World!");
+    /// let source = "Hi {{name}}, bye.";
+    /// let mut template = ZipTemplate::parse(source);
+    /// template.reparse_range(source, 13..16, "see you {{when}}");
+    /// assert_eq!(template.statics, ["Hi ", ", see you ", "."]);
+    /// assert_eq!(template.placeholders, ["name", "when", ""]);
     /// ```
-    pub fn render(&self, flat: &FxHashMap<String, String>) -> String {
-        let mut out = String::with_capacity(self.pre_emptive_size);
-        let dynamics = self
-            .placeholders
-            .iter()
-            .map(|placeholder| flat.get(placeholder).map_or("", |s| s.as_str()));
+    pub fn reparse_range(&mut self, original: &str, edited_range: Range<usize>, new_text: &str) {
+        let Range { start, end } = edited_range;
+        let edited_len = original.len() - (end - start) + new_text.len();
+        self.pre_emptive_size = (self.pre_emptive_size + edited_len).saturating_sub(original.len());
 
-        self.statics.iter().zip(dynamics).for_each(|(s, dynamic)| {
-            out.push_str(s);
-            out.push_str(dynamic);
-        });
+        let Some(tags) = self.tag_spans(original) else {
+            let edited = [&original[..start], new_text, &original[end..]].concat();
+            let reparsed = ZipTemplate::parse(&edited);
+            self.statics = reparsed.statics;
+            self.placeholders = reparsed.placeholders;
+            return;
+        };
 
-        out
+        // Tags entirely before the edit, and the first tag entirely after it.
+        let first = tags.iter().take_while(|tag| tag.end <= start).count();
+        let mut next = first
+            + tags[first..]
+                .iter()
+                .take_while(|tag| tag.start < end)
+                .count();
+        let region_start = first.checked_sub(1).map_or(0, |i| tags[i].end);
+
+        let region = loop {
+            let region_end = tags.get(next).map_or(original.len(), |tag| tag.start);
+            let text = [
+                &original[region_start..start],
+                new_text,
+                &original[end..region_end],
+            ]
+            .concat();
+            let region = ZipTemplate::parse_with_capacity(&text, 0);
+
+            let open_tail = region
+                .statics
+                .last()
+                .is_some_and(|s| s.contains("{{") || s.ends_with('{'));
+            if next == tags.len() || !open_tail {
+                break region;
+            }
+            next += 1;
+        };
+
+        let new_placeholders = region.statics.len() - 1;
+        self.statics.splice(first..=next, region.statics);
+        self.placeholders.splice(
+            first..next,
+            region.placeholders.into_iter().take(new_placeholders),
+        );
     }
 
-    /// Renders a template by interleaving the stored static segments with the provided
-    /// dynamic values.
+    /// Locates the byte span of every tag in `original`, the source this template
+    /// was parsed from, or `None` if the template does not match it.
+    fn tag_spans(&self, original: &str) -> Option<Vec<Range<usize>>> {
+        let (open, close) = DELIMITERS[0];
+        let mut spans = Vec::with_capacity(self.statics.len().saturating_sub(1));
+        let mut pos = 0;
+
+        for s in &self.statics[..self.real_placeholders().len()] {
+            let tag_start = pos + s.len();
+            if original.get(pos..tag_start)? != s || !original[tag_start..].starts_with(open) {
+                return None;
+            }
+            let content_start = tag_start + open.len();
+            let tag_end = content_start + original[content_start..].find(close)? + close.len();
+            spans.push(tag_start..tag_end);
+            pos = tag_end;
+        }
+
+        match self.statics.last() {
+            Some(last) if original[pos..] == *last => Some(spans),
+            _ => None,
+        }
+    }
+
+    /// Returns the distinct placeholder keys in order of first appearance.
     ///
-    /// This function iterates through the internal static parts of the template and
-    /// appends a dynamic value after each part, consuming the `dynamics` slice strictly
-    /// in order.
+    /// The alignment slot is not included.
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    /// * `dynamics` - A slice of strings to insert between static segments.
+    /// ```
+    /// use zip_templates::ZipTemplate;
     ///
-    /// # Returns
+    /// let template = ZipTemplate::parse("{{a}} {{b}} {{a}}");
+    /// assert_eq!(template.unique_placeholders(), ["a", "b"]);
+    /// ```
+    pub fn unique_placeholders(&self) -> Vec<&str> {
+        let mut seen = FxHashSet::default();
+        self.real_placeholders()
+            .iter()
+            .map(String::as_str)
+            .filter(|key| seen.insert(*key))
+            .collect()
+    }
+
+    /// Returns the distinct `|>` filter names used across all placeholders, in order
+    /// of first appearance.
     ///
-    /// A new `String` containing the rendered content.
+    /// Useful to check up front that every filter a template references is known
+    /// before rendering it.
     ///
     /// # Examples
     ///
     /// ```
     /// use zip_templates::ZipTemplate;
     ///
-    /// let template = ZipTemplate::parse(
-    ///    "Hello {{name}}!"
-    /// );
+    /// let template = ZipTemplate::parse("{{name |> trim |> upper}} {{city |> upper}}");
+    /// assert_eq!(template.used_filters(), ["trim", "upper"]);
+    /// ```
+    pub fn used_filters(&self) -> Vec<&str> {
+        let mut seen = FxHashSet::default();
+        self.real_placeholders()
+            .iter()
+            .flat_map(|placeholder| tag::Tag::parse(placeholder).filters())
+            .filter(|name| seen.insert(*name))
+            .collect()
+    }
+
+    /// Returns the static text separating the first `{{key_a}}` from the placeholder
+    /// that follows it, if that placeholder is `{{key_b}}`.
     ///
-    /// let args = vec!["World".to_string()];
-    /// let result = template.render_from_vec(&args);
+    /// Returns `None` if `key_a` does not occur or is not immediately followed by
+    /// `key_b`.
+    ///
+    /// # Examples
     ///
-    /// assert_eq!(result, "Hello World!");
     /// ```
-    pub fn render_from_vec(&self, dynamics: &[String]) -> String {
-        let mut out = String::with_capacity(self.pre_emptive_size);
-
-        let mut dynamics_iter = dynamics.iter();
-
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse("{{last}}, {{first}}");
+    /// assert_eq!(template.static_between("last", "first"), Some(", "));
+    /// assert_eq!(template.static_between("first", "last"), None);
+    /// ```
+    pub fn static_between(&self, key_a: &str, key_b: &str) -> Option<&str> {
+        let placeholders = self.real_placeholders();
+        let index = placeholders.iter().position(|key| key == key_a)?;
+        match placeholders.get(index + 1) {
+            Some(next) if next == key_b => Some(&self.statics[index + 1]),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if rendering against `a` and `b` would produce the same output.
+    ///
+    /// Only the keys the template references are compared, so differences in
+    /// unrelated entries are ignored. Nothing is rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut a = FxHashMap::default();
+    /// a.insert("name".to_string(), "World".to_string());
+    /// let mut b = a.clone();
+    /// b.insert("unused".to_string(), "x".to_string());
+    ///
+    /// assert!(template.renders_same(&a, &b));
+    /// ```
+    pub fn renders_same(
+        &self,
+        a: &FxHashMap<String, String>,
+        b: &FxHashMap<String, String>,
+    ) -> bool {
+        let (a, b) = (self.resolver(a), self.resolver(b));
+        self.unique_placeholders()
+            .into_iter()
+            .all(|key| a.get(key) == b.get(key))
+    }
+
+    /// Returns the distinct placeholders whose value differs between `before` and
+    /// `after`, in order of first appearance.
+    ///
+    /// A placeholder changes when it resolves to a different value, or when it
+    /// starts or stops resolving (see [`ZipTemplate::render_strict`]) even if it
+    /// renders empty either way. Only the keys the template references are
+    /// compared, so a live view can update just the affected spots without
+    /// rendering twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("{{user}} has {{count}} messages");
+    /// let mut before = FxHashMap::default();
+    /// before.insert("user".to_string(), "Sam".to_string());
+    /// before.insert("count".to_string(), "4".to_string());
+    /// let mut after = before.clone();
+    /// after.insert("count".to_string(), "5".to_string());
+    ///
+    /// assert_eq!(template.changed_placeholders(&before, &after), ["count"]);
+    /// ```
+    pub fn changed_placeholders(
+        &self,
+        before: &FxHashMap<String, String>,
+        after: &FxHashMap<String, String>,
+    ) -> Vec<&str> {
+        let (before, after) = (self.resolver(before), self.resolver(after));
+        self.unique_placeholders()
+            .into_iter()
+            .filter(|key| {
+                before.get(key) != after.get(key)
+                    || before.is_resolved(key) != after.is_resolved(key)
+            })
+            .collect()
+    }
+
+    /// Returns `true` if both templates have the same placeholders in the same order,
+    /// whatever their static text.
+    ///
+    /// Placeholders are compared by their full trimmed content, so `{{name}}` and
+    /// `{{name | anonymous}}` differ. Since every placeholder sits between two
+    /// statics, equal placeholder sequences also mean equally many statics. This is
+    /// the check translation tooling needs to keep the variables of localized
+    /// variants in a stable order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let en = ZipTemplate::parse("Hello {{name}}, you have {{count}} messages");
+    /// let de = ZipTemplate::parse("Hallo {{name}}, du hast {{count}} Nachrichten");
+    /// let fr = ZipTemplate::parse("{{count}} messages pour {{name}}");
+    /// assert!(en.shares_skeleton(&de));
+    /// assert!(!en.shares_skeleton(&fr));
+    /// ```
+    pub fn shares_skeleton(&self, other: &ZipTemplate) -> bool {
+        self.real_placeholders() == other.real_placeholders()
+    }
+
+    /// Returns a shorter template whose static content totals at most `max_static_bytes`.
+    ///
+    /// Statics are kept in order until the budget runs out; the static that exhausts
+    /// it is cut (on a char boundary) and becomes the last one, and every segment
+    /// after it is dropped, including the placeholder that would have followed it.
+    /// The result is a valid template, e.g. for "read more" previews: its output is
+    /// at most `max_static_bytes` plus the length of its remaining values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse("Dear {{name}}, thank you for your order.");
+    /// let preview = template.truncate_static(12);
+    /// assert_eq!(preview.statics, ["Dear ", ", thank"]);
+    /// assert_eq!(preview.placeholders, ["name", ""]);
+    /// ```
+    pub fn truncate_static(&self, max_static_bytes: usize) -> ZipTemplate {
+        let mut statics = Vec::new();
+        let mut placeholders = Vec::new();
+        let mut remaining = max_static_bytes;
+
+        for (i, s) in self.statics.iter().enumerate() {
+            let is_last = i + 1 == self.statics.len();
+            if is_last || s.len() >= remaining {
+                let mut cut = remaining.min(s.len());
+                while !s.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                statics.push(s[..cut].to_string());
+                break;
+            }
+
+            statics.push(s.clone());
+            placeholders.push(self.placeholders.get(i).cloned().unwrap_or_default());
+            remaining -= s.len();
+        }
+        placeholders.push(String::new());
+
+        ZipTemplate {
+            statics,
+            placeholders,
+            pre_emptive_size: self.pre_emptive_size,
+            computed: self.computed.clone(),
+        }
+    }
+
+    /// Binds the placeholders `known` has values for, returning a template with only
+    /// the remaining placeholders.
+    ///
+    /// A placeholder is bound when `known` settles its value: its key is in `known`,
+    /// or, for a `??` chain, some key has a non-empty value in `known` and every key
+    /// before it is in `known` with an empty value. Its value is resolved as in
+    /// [`ZipTemplate::render`] and merged into the surrounding statics. A `|default`
+    /// alone does not bind, and neither does a later key of a chain while an earlier
+    /// one is unknown, so a value supplied later still takes precedence. Rendering
+    /// the result with the remaining values gives the same output as rendering
+    /// `self` with all of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("{{greeting}}, {{name}}!");
+    /// let mut early = FxHashMap::default();
+    /// early.insert("greeting".to_string(), "Hello".to_string());
+    ///
+    /// let filled = template.fill(&early);
+    /// assert_eq!(filled.statics, ["Hello, ", "!"]);
+    /// assert_eq!(filled.placeholders, ["name", ""]);
+    /// ```
+    pub fn fill(&self, known: &FxHashMap<String, String>) -> ZipTemplate {
+        let mut statics = Vec::new();
+        let mut placeholders = Vec::new();
+        let mut current = String::new();
+        let mut remaining = self.real_placeholders().iter();
+
+        for s in self.statics.iter() {
+            current.push_str(s);
+
+            let Some(placeholder) = remaining.next() else {
+                continue;
+            };
+            // The first key that is unknown or non-empty decides the chain; an
+            // unknown one could still be supplied later.
+            // Computed keys are only known at render time.
+            let bound = !self.is_computed(placeholder)
+                && (known.contains_key(placeholder.as_str())
+                    || (placeholder.contains(['?', '|', ':', '\\'])
+                        && !matches!(
+                            tag::Tag::parse(placeholder)
+                                .keys()
+                                .map(|key| match self.is_computed(&key) {
+                                    true => None,
+                                    false => known.get(key.as_ref()),
+                                })
+                                .find(|value| !value.is_some_and(String::is_empty)),
+                            Some(None)
+                        )));
+            if bound {
+                current.push_str(&lookup(known, placeholder));
+            } else {
+                statics.push(std::mem::take(&mut current));
+                placeholders.push(placeholder.clone());
+            }
+        }
+        statics.push(current);
+        placeholders.push(String::new());
+
+        ZipTemplate {
+            statics,
+            placeholders,
+            pre_emptive_size: self.pre_emptive_size,
+            computed: self.computed.clone(),
+        }
+    }
+
+    /// Repairs malformed dot-paths in the placeholder keys: empty segments are
+    /// removed and the remaining segments trimmed, so `user..name`, `.user.name`
+    /// and `user . name.` all become the `user.name` form.
+    ///
+    /// Each key of a `??` chain is normalized; specs, filters and defaults are left
+    /// as they are, as are tags starting with a sigil (`#`, `/`, `>`, `!`, `@`, `*`).
+    /// This changes which data keys the template matches: a map that really has a
+    /// `user..name` entry no longer fills the placeholder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let mut template = ZipTemplate::parse("{{user..name}} {{.id | none}}");
+    /// template.normalize_paths();
+    /// assert_eq!(template.placeholders, ["user.name", "id | none", ""]);
+    /// ```
+    pub fn normalize_paths(&mut self) {
+        fn normalize_path(path: &str) -> String {
+            path.split('.')
+                .map(str::trim)
+                .filter(|segment| !segment.is_empty())
+                .collect::<Vec<_>>()
+                .join(".")
+        }
+
+        let count = self.real_placeholders().len();
+        for placeholder in &mut self.placeholders[..count] {
+            if tag::is_sigil_tag(placeholder) {
+                continue;
+            }
+
+            let key = tag::Tag::parse(placeholder).key;
+            let rest = &placeholder[key.len()..];
+            let (prefix, chain) = match key.strip_prefix('?') {
+                Some(chain) if !key.starts_with("??") => ("?", chain),
+                _ => ("", key),
+            };
+            let chain = match chain.contains("??") {
+                true => chain
+                    .split("??")
+                    .map(normalize_path)
+                    .collect::<Vec<_>>()
+                    .join(" ?? "),
+                false => normalize_path(chain),
+            };
+
+            let normalized = format!("{}{}{}", prefix, chain, rest);
+            if normalized != *placeholder {
+                *placeholder = normalized;
+            }
+        }
+    }
+
+    /// The placeholders that correspond to actual `{{ }}` tags, i.e. without the
+    /// empty alignment slot paired with the trailing static.
+    fn real_placeholders(&self) -> &[String] {
+        let count = self.statics.len().saturating_sub(1);
+        &self.placeholders[..count.min(self.placeholders.len())]
+    }
+
+    /// Renders a template by resolving placeholders against a provided map of values.
+    ///
+    /// This function efficiently assembles a final string by interleaving the static parts
+    /// of the template with dynamic values looked up from the provided `flat` map.
+    /// Keys with a function registered through [`ZipTemplate::register_computed`]
+    /// take their value from that function instead.
+    ///
+    /// # Allocation Strategy
+    ///
+    /// The output buffer is pre-allocated using `self.pre_emptive_size` to minimize reallocations.
+    ///
+    /// # Arguments
+    ///
+    /// * `flat` - A map containing placeholder keys and their replacement values. If a key is
+    ///   missing in the map, it defaults to an empty string.
+    ///
+    /// # Returns
+    ///
+    /// A new `String` containing the fully rendered content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let rendered = template.render(&values);
+    /// assert_eq!(rendered, "Hello, World!");
+    /// ```
+    pub fn render(&self, flat: &FxHashMap<String, String>) -> String {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let dynamics = self
+            .placeholders
+            .iter()
+            .map(|placeholder| resolver.get(placeholder));
+
+        self.statics.iter().zip(dynamics).for_each(|(s, dynamic)| {
+            out.push_str(s);
+            out.push_str(&dynamic);
+        });
+
+        out
+    }
+
+    /// Renders a template into a buffer of exactly the output size, for templates
+    /// dominated by a few large statics.
+    ///
+    /// All values are resolved first, so the output length is known before anything
+    /// is copied; each segment is then a single bulk copy and the buffer is never
+    /// grown or over-allocated. For templates with many placeholders the extra pass
+    /// costs more than it saves, and [`ZipTemplate::render`] is faster. The output
+    /// is identical to [`ZipTemplate::render`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let page = format!("<html>{}{{{{body}}}}</html>", " ".repeat(4096));
+    /// let template = ZipTemplate::parse(&page);
+    /// let mut values = FxHashMap::default();
+    /// values.insert("body".to_string(), "Hi".to_string());
+    ///
+    /// let rendered = template.render_bulk(&values);
+    /// assert_eq!(rendered, template.render(&values));
+    /// assert_eq!(rendered.capacity(), rendered.len());
+    /// ```
+    pub fn render_bulk(&self, flat: &FxHashMap<String, String>) -> String {
+        self.render_with_scratch(flat, &mut Vec::new())
+    }
+
+    /// Renders a template like [`ZipTemplate::render_bulk`], resolving the values
+    /// into the caller's `scratch` buffer instead of a new `Vec`.
+    ///
+    /// `scratch` is cleared and refilled on every call, so rendering in a loop with
+    /// the same buffer allocates only the output once it has grown to the number of
+    /// placeholders. Values are borrowed from `flat` and the template; only a
+    /// `|default` containing escapes is owned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("{{greeting}}, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("greeting".to_string(), "Hello".to_string());
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let mut scratch = Vec::new();
+    /// for _ in 0..3 {
+    ///     assert_eq!(template.render_with_scratch(&values, &mut scratch), "Hello, World!");
+    /// }
+    /// ```
+    pub fn render_with_scratch<'a>(
+        &'a self,
+        flat: &'a FxHashMap<String, String>,
+        scratch: &mut Vec<Cow<'a, str>>,
+    ) -> String {
+        let resolver = self.resolver(flat);
+        scratch.clear();
+        scratch.extend(
+            self.placeholders
+                .iter()
+                .map(|placeholder| resolver.get(placeholder)),
+        );
+        let len = self.statics.iter().map(String::len).sum::<usize>()
+            + scratch.iter().map(|value| value.len()).sum::<usize>();
+
+        let mut out = String::with_capacity(len);
+        for (s, value) in self.statics.iter().zip(scratch.iter()) {
+            out.push_str(s);
+            out.push_str(value);
+        }
+
+        out
+    }
+
+    /// Renders a template and passes the whole output through `finalize`.
+    ///
+    /// Suited to post-processing that is cheaper on the assembled output than per
+    /// segment, such as minification or whitespace cleanup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let rendered = template.render_then(&values, |out| out.to_uppercase());
+    /// assert_eq!(rendered, "HELLO, WORLD!");
+    /// ```
+    pub fn render_then<F>(&self, flat: &FxHashMap<String, String>, finalize: F) -> String
+    where
+        F: FnOnce(String) -> String,
+    {
+        finalize(self.render(flat))
+    }
+
+    /// Renders a template like [`ZipTemplate::render`], refusing templates that
+    /// contain control-flow tags.
+    ///
+    /// The flat renderers treat a block tag such as `{{#each items}}` or `{{/each}}`
+    /// as an ordinary (missing) key, which silently renders a loop body once with
+    /// the tags blanked out. A placeholder whose content starts with `#` or `/` is a
+    /// control tag; this method reports the first one instead of rendering.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ControlTagError`] for the first control tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let values = FxHashMap::default();
+    /// assert!(ZipTemplate::parse("Hello").render_flat(&values).is_ok());
+    ///
+    /// let template = ZipTemplate::parse("{{#each items}}{{name}}{{/each}}");
+    /// assert_eq!(template.render_flat(&values).unwrap_err().tag, "#each items");
+    /// ```
+    pub fn render_flat(&self, flat: &FxHashMap<String, String>) -> Result<String, ControlTagError> {
+        let control = self
+            .real_placeholders()
+            .iter()
+            .position(|placeholder| placeholder.starts_with(['#', '/']));
+        match control {
+            Some(index) => Err(ControlTagError {
+                index,
+                tag: self.placeholders[index].clone(),
+            }),
+            None => Ok(self.render(flat)),
+        }
+    }
+
+    /// Renders a template against a map of borrowed or owned values, as produced by
+    /// [`flatten_json_borrowed`].
+    ///
+    /// Resolution is identical to [`ZipTemplate::render`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use zip_templates::{flatten_json_borrowed, ZipTemplate};
+    ///
+    /// let data = json!({ "name": "World" });
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// assert_eq!(template.render_borrowed(&flatten_json_borrowed(&data)), "Hello, World!");
+    /// ```
+    pub fn render_borrowed(&self, flat: &FxHashMap<String, Cow<'_, str>>) -> String {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let dynamics = self
+            .placeholders
+            .iter()
+            .map(|placeholder| resolver.get(placeholder));
+
+        self.statics.iter().zip(dynamics).for_each(|(s, dynamic)| {
+            out.push_str(s);
+            out.push_str(&dynamic);
+        });
+
+        out
+    }
+
+    /// Renders a template by resolving placeholders directly in a JSON value,
+    /// without flattening it first.
+    ///
+    /// A placeholder's dot-path walks object keys and array indices. Scalars render
+    /// as [`flatten_json`] would render them; `??` alternatives and `|default`s
+    /// apply as in [`ZipTemplate::render`]. A placeholder prefixed with `*`, such as
+    /// `{{*user}}`, instead inlines the compact JSON serialization of the whole
+    /// subtree at its path (`{{*}}` inlines `data` itself). Missing paths, and
+    /// objects or arrays without `*`, render empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let data = json!({ "user": { "name": "Alice", "tags": ["admin"] } });
+    /// let template = ZipTemplate::parse("{{user.name}}: {{*user.tags}}");
+    /// assert_eq!(template.render_json(&data), r#"Alice: ["admin"]"#);
+    /// ```
+    pub fn render_json(&self, data: &Value) -> String {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        let mut placeholders = self.real_placeholders().iter();
+
         for s in self.statics.iter() {
             out.push_str(s);
 
-            if let Some(dynamic) = dynamics_iter.next() {
-                out.push_str(dynamic);
+            let Some(placeholder) = placeholders.next() else {
+                continue;
+            };
+            match placeholder.strip_prefix('*') {
+                Some(path) => {
+                    if let Some(subtree) = json_path(data, path.trim()) {
+                        out.push_str(&subtree.to_string());
+                    }
+                }
+                None => out.push_str(&json_lookup(data, placeholder)),
+            }
+        }
+
+        out
+    }
+
+    /// Renders a template like [`ZipTemplate::render_json`], choosing with `policy`
+    /// what a placeholder leading to an object or an array renders.
+    ///
+    /// `{{user}}` against `{"user": {"name": "Alice"}}` renders empty with
+    /// [`ObjectPolicy::Empty`], `{"name":"Alice"}` with [`ObjectPolicy::Json`] and
+    /// `Alice` with [`ObjectPolicy::FirstField`]. In a `??` chain the non-scalar
+    /// value counts as a value: it wins over later alternatives unless it renders
+    /// empty. `{{*path}}` placeholders inline the subtree regardless of `policy`.
+    ///
+    /// # Errors
+    ///
+    /// With [`ObjectPolicy::Error`], returns a [`NonScalarError`] for the first
+    /// placeholder whose resolution reaches an object or an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use zip_templates::{ObjectPolicy, ZipTemplate};
+    ///
+    /// let data = json!({ "user": { "name": "Alice" } });
+    /// let template = ZipTemplate::parse("Hi {{user}}");
+    /// assert_eq!(template.render_json_with(&data, ObjectPolicy::Empty).unwrap(), "Hi ");
+    /// assert_eq!(
+    ///     template.render_json_with(&data, ObjectPolicy::Json).unwrap(),
+    ///     r#"Hi {"name":"Alice"}"#
+    /// );
+    /// assert!(template.render_json_with(&data, ObjectPolicy::Error).is_err());
+    /// ```
+    pub fn render_json_with(
+        &self,
+        data: &Value,
+        policy: ObjectPolicy,
+    ) -> Result<String, NonScalarError> {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        let mut placeholders = self.real_placeholders().iter().enumerate();
+
+        for s in self.statics.iter() {
+            out.push_str(s);
+
+            let Some((index, placeholder)) = placeholders.next() else {
+                continue;
+            };
+            match placeholder.strip_prefix('*') {
+                Some(path) => {
+                    if let Some(subtree) = json_path(data, path.trim()) {
+                        out.push_str(&subtree.to_string());
+                    }
+                }
+                None => {
+                    let value = json_lookup_with(data, placeholder, policy)
+                        .map_err(|key| NonScalarError { index, key })?;
+                    out.push_str(&value);
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Recommends the cheapest way to render this template against `value`, from the
+    /// number of distinct placeholders compared to the number of leaves in `value`.
+    ///
+    /// Flattening costs a key and a value string per leaf, whereas resolving paths
+    /// directly costs a walk per placeholder. The heuristic is:
+    ///
+    /// - [`RenderStrategy::FlattenAll`] when the template references at least half
+    ///   as many keys as the document has leaves;
+    /// - [`RenderStrategy::DirectPaths`] when the document has more than 20 leaves
+    ///   per referenced key;
+    /// - [`RenderStrategy::PartialFlatten`] in between.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use zip_templates::{RenderStrategy, ZipTemplate};
+    ///
+    /// let template = ZipTemplate::parse("{{name}} ({{age}})");
+    /// let data = json!({ "name": "Sam", "age": 30, "city": "Paris" });
+    /// assert_eq!(template.recommend_strategy(&data), RenderStrategy::FlattenAll);
+    /// ```
+    pub fn recommend_strategy(&self, value: &Value) -> RenderStrategy {
+        fn count_leaves(value: &Value) -> usize {
+            match value {
+                Value::Object(map) => map.values().map(count_leaves).sum(),
+                Value::Array(items) => items.iter().map(count_leaves).sum(),
+                _ => 1,
+            }
+        }
+
+        let keys = self.unique_placeholders().len();
+        let leaves = count_leaves(value);
+        if keys * 2 >= leaves {
+            RenderStrategy::FlattenAll
+        } else if leaves > keys * 20 {
+            RenderStrategy::DirectPaths
+        } else {
+            RenderStrategy::PartialFlatten
+        }
+    }
+
+    /// Renders a template, first translating each placeholder through `aliases`.
+    ///
+    /// A placeholder found in `aliases` is replaced by the key it maps to before the
+    /// lookup in `data`; other placeholders are looked up as they are. This keeps the
+    /// template vocabulary independent from the data schema.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{first_name}}!");
+    /// let mut aliases = FxHashMap::default();
+    /// aliases.insert("first_name".to_string(), "user.name.first".to_string());
+    /// let mut data = FxHashMap::default();
+    /// data.insert("user.name.first".to_string(), "Sam".to_string());
+    ///
+    /// assert_eq!(template.render_with_aliases(&aliases, &data), "Hello, Sam!");
+    /// ```
+    pub fn render_with_aliases(
+        &self,
+        aliases: &FxHashMap<String, String>,
+        data: &FxHashMap<String, String>,
+    ) -> String {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let resolver = self.resolver(data);
+        let dynamics = self.placeholders.iter().map(|placeholder| {
+            let key = aliases.get(placeholder).unwrap_or(placeholder);
+            resolver.get(key)
+        });
+
+        self.statics.iter().zip(dynamics).for_each(|(s, dynamic)| {
+            out.push_str(s);
+            out.push_str(&dynamic);
+        });
+
+        out
+    }
+
+    /// Renders a template with values supplied by `resolve`, timing every call.
+    ///
+    /// `resolve` is called once per placeholder, in template order, with the
+    /// placeholder key; `None` renders as an empty string. Alongside the output, the
+    /// time spent in each call is returned keyed by placeholder, which makes it easy
+    /// to spot slow resolvers (database lookups, remote calls) in a render.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse("{{greeting}}, {{name}}!");
+    /// let (rendered, timings) =
+    ///     template.render_with_timing(|key| Some(key.to_uppercase()));
+    ///
+    /// assert_eq!(rendered, "GREETING, NAME!");
+    /// assert_eq!(timings[1].0, "name");
+    /// ```
+    pub fn render_with_timing<F>(&self, mut resolve: F) -> (String, Vec<(String, Duration)>)
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let mut timings = Vec::with_capacity(self.statics.len().saturating_sub(1));
+        let mut placeholders = self.real_placeholders().iter();
+
+        for s in self.statics.iter() {
+            out.push_str(s);
+
+            if let Some(key) = placeholders.next() {
+                let start = Instant::now();
+                let value = resolve(key);
+                timings.push((key.clone(), start.elapsed()));
+
+                if let Some(value) = value {
+                    out.push_str(&value);
+                }
+            }
+        }
+
+        (out, timings)
+    }
+
+    /// Renders a template routing each placeholder to a value provider by key prefix.
+    ///
+    /// Each placeholder goes to the provider whose prefix it starts with, the longest
+    /// prefix winning, and the provider is called with the rest of the key after the
+    /// prefix. Keys matching no prefix, and providers returning `None`, render empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let env = |key: &str| (key == "HOME").then(|| "/home/sam".to_string());
+    /// let constant = |key: &str| Some(key.to_uppercase());
+    ///
+    /// let template = ZipTemplate::parse("{{env.HOME}}/{{const.app}}");
+    /// let rendered = template.render_with_providers(&[("env.", &env), ("const.", &constant)]);
+    /// assert_eq!(rendered, "/home/sam/APP");
+    /// ```
+    pub fn render_with_providers(&self, providers: &[(&str, &ValueProvider)]) -> String {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let mut placeholders = self.real_placeholders().iter();
+
+        for s in self.statics.iter() {
+            out.push_str(s);
+
+            let Some(key) = placeholders.next() else {
+                continue;
+            };
+            let routed = providers
+                .iter()
+                .filter_map(|(prefix, provider)| {
+                    Some((prefix.len(), key.strip_prefix(prefix)?, provider))
+                })
+                .max_by_key(|(len, _, _)| *len);
+            if let Some(value) = routed.and_then(|(_, rest, provider)| provider(rest)) {
+                out.push_str(&value);
+            }
+        }
+
+        out
+    }
+
+    /// Renders a template with values supplied by a [`DynContext`] trait object.
+    ///
+    /// `ctx` is asked once per placeholder, in template order, with the placeholder
+    /// key; the value is written through its [`fmt::Display`] implementation and
+    /// `None` renders as an empty string. Values of unrelated types can thus come
+    /// from plugins known only at runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fmt::Display;
+    /// use zip_templates::{DynContext, ZipTemplate};
+    ///
+    /// struct Stats;
+    ///
+    /// impl DynContext for Stats {
+    ///     fn get(&self, key: &str) -> Option<Box<dyn Display>> {
+    ///         match key {
+    ///             "users" => Some(Box::new(42)),
+    ///             "load" => Some(Box::new(0.75)),
+    ///             _ => None,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let template = ZipTemplate::parse("{{users}} users, load {{load}}");
+    /// assert_eq!(template.render_dyn(&Stats), "42 users, load 0.75");
+    /// ```
+    pub fn render_dyn(&self, ctx: &dyn DynContext) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let mut placeholders = self.real_placeholders().iter();
+
+        for s in self.statics.iter() {
+            out.push_str(s);
+
+            if let Some(value) = placeholders.next().and_then(|key| ctx.get(key)) {
+                // Writing to a `String` cannot fail.
+                let _ = write!(out, "{}", value);
+            }
+        }
+
+        out
+    }
+
+    /// Renders the template once per row of parallel `columns`, joined by `separator`.
+    ///
+    /// Row `i` resolves each placeholder to `columns[key][i]`; placeholders without a
+    /// column render empty. This suits tabular data stored column by column, without
+    /// needing loop syntax in the template. The number of rows is the length of the
+    /// referenced columns, so when the template references no column there are no
+    /// rows and the result is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ColumnLengthError`] if the columns referenced by the template do
+    /// not all have the same length. Unreferenced columns are not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("{{name}}={{qty}}");
+    /// let mut columns = FxHashMap::default();
+    /// columns.insert("name".to_string(), vec!["apples".to_string(), "pears".to_string()]);
+    /// columns.insert("qty".to_string(), vec!["3".to_string(), "5".to_string()]);
+    ///
+    /// assert_eq!(template.render_rows(&columns, ", ").unwrap(), "apples=3, pears=5");
+    /// ```
+    pub fn render_rows(
+        &self,
+        columns: &FxHashMap<String, Vec<String>>,
+        separator: &str,
+    ) -> Result<String, ColumnLengthError> {
+        let referenced: Vec<Option<&Vec<String>>> = self
+            .real_placeholders()
+            .iter()
+            .map(|key| columns.get(key))
+            .collect();
+
+        let mut rows = None;
+        for (key, column) in self.real_placeholders().iter().zip(referenced.iter()) {
+            if let Some(column) = column {
+                let expected = *rows.get_or_insert(column.len());
+                if column.len() != expected {
+                    return Err(ColumnLengthError {
+                        key: key.clone(),
+                        len: column.len(),
+                        expected,
+                    });
+                }
+            }
+        }
+        let rows = rows.unwrap_or(0);
+
+        let mut out = String::with_capacity(self.pre_emptive_size * rows);
+        for row in 0..rows {
+            if row > 0 {
+                out.push_str(separator);
+            }
+
+            let mut values = referenced.iter();
+            for s in self.statics.iter() {
+                out.push_str(s);
+                if let Some(Some(column)) = values.next() {
+                    out.push_str(&column[row]);
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Lazily renders the template once per data map yielded by `rows`.
+    ///
+    /// Nothing is rendered until the returned iterator is polled, so it composes with
+    /// `filter`, `take` and friends over unbounded inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("#{{n}}");
+    /// let rows = (1..).map(|n| {
+    ///     let mut row = FxHashMap::default();
+    ///     row.insert("n".to_string(), n.to_string());
+    ///     row
+    /// });
+    ///
+    /// let first: Vec<String> = template.render_stream(rows).take(2).collect();
+    /// assert_eq!(first, ["#1", "#2"]);
+    /// ```
+    pub fn render_stream<'a, I>(&'a self, rows: I) -> impl Iterator<Item = String> + 'a
+    where
+        I: IntoIterator<Item = FxHashMap<String, String>> + 'a,
+    {
+        rows.into_iter().map(move |row| self.render(&row))
+    }
+
+    /// Renders only the statics with indices in `static_range`, each followed by its
+    /// placeholder's value.
+    ///
+    /// Rendering consecutive ranges and concatenating the results gives the same
+    /// output as [`ZipTemplate::render`], so a large document can be produced in
+    /// windows. Bounds past the last static are clamped; an empty or inverted range
+    /// renders nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("<h1>{{title}}</h1><p>{{body}}</p>");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("title".to_string(), "News".to_string());
+    /// values.insert("body".to_string(), "Nothing new".to_string());
+    ///
+    /// assert_eq!(template.render_range(&values, 0..1), "<h1>News");
+    /// assert_eq!(template.render_range(&values, 1..10), "</h1><p>Nothing new</p>");
+    /// ```
+    pub fn render_range(
+        &self,
+        flat: &FxHashMap<String, String>,
+        static_range: Range<usize>,
+    ) -> String {
+        let resolver = self.resolver(flat);
+        let end = static_range
+            .end
+            .min(self.statics.len())
+            .min(self.placeholders.len());
+        let start = static_range.start.min(end);
+        let mut out = String::new();
+
+        for (s, placeholder) in self.statics[start..end]
+            .iter()
+            .zip(self.placeholders[start..end].iter())
+        {
+            out.push_str(s);
+            out.push_str(&resolver.get(placeholder));
+        }
+
+        out
+    }
+
+    /// Renders a template into byte chunks of `chunk_size` bytes, e.g. for chunked
+    /// transfer encoding.
+    ///
+    /// Every chunk but the last is exactly `chunk_size` bytes; the last holds the
+    /// remainder and no empty chunk is produced. The output is cut on byte
+    /// boundaries, so a multi-byte character may be split between two chunks:
+    /// only the concatenation of the chunks is guaranteed to be valid UTF-8.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let chunks = template.render_into_chunks(&values, 5);
+    /// assert_eq!(chunks, [&b"Hello"[..], b", Wor", b"ld!"]);
+    /// ```
+    pub fn render_into_chunks(
+        &self,
+        flat: &FxHashMap<String, String>,
+        chunk_size: usize,
+    ) -> Vec<Vec<u8>> {
+        let resolver = self.resolver(flat);
+        assert!(chunk_size > 0, "chunk_size must be non-zero");
+
+        // A huge `chunk_size` must not be allocated up front.
+        let chunk_capacity = chunk_size.min(self.pre_emptive_size);
+        let mut chunks = Vec::with_capacity(self.pre_emptive_size / chunk_size + 1);
+        let mut chunk = Vec::with_capacity(chunk_capacity);
+
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            for segment in [Cow::Borrowed(s.as_str()), resolver.get(placeholder)] {
+                let mut bytes = segment.as_bytes();
+                while !bytes.is_empty() {
+                    let take = bytes.len().min(chunk_size - chunk.len());
+                    chunk.extend_from_slice(&bytes[..take]);
+                    bytes = &bytes[take..];
+                    if chunk.len() == chunk_size {
+                        chunks.push(std::mem::replace(
+                            &mut chunk,
+                            Vec::with_capacity(chunk_size),
+                        ));
+                    }
+                }
+            }
+        }
+
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+        chunks
+    }
+
+    /// Renders a template into any [`fmt::Write`] sink instead of a new `String`.
+    ///
+    /// Resolution is identical to [`ZipTemplate::render`]; each segment is written
+    /// as soon as it is produced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let mut out = String::from("> ");
+    /// template.render_into(&values, &mut out).unwrap();
+    /// assert_eq!(out, "> Hello, World!");
+    /// ```
+    pub fn render_into<W: fmt::Write>(
+        &self,
+        flat: &FxHashMap<String, String>,
+        out: &mut W,
+    ) -> fmt::Result {
+        let resolver = self.resolver(flat);
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.write_str(s)?;
+            out.write_str(&resolver.get(placeholder))?;
+        }
+        Ok(())
+    }
+
+    /// Renders a template into two sinks at once, in a single pass.
+    ///
+    /// Every segment is written to `a` and then to `b`, so neither a second render
+    /// nor a copy of the full output is needed, e.g. to log a response while
+    /// sending it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let (mut response, mut log) = (String::new(), String::new());
+    /// template.render_tee(&values, &mut response, &mut log).unwrap();
+    /// assert_eq!(response, log);
+    /// ```
+    pub fn render_tee<W1: fmt::Write, W2: fmt::Write>(
+        &self,
+        flat: &FxHashMap<String, String>,
+        a: &mut W1,
+        b: &mut W2,
+    ) -> fmt::Result {
+        let resolver = self.resolver(flat);
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            let value = resolver.get(placeholder);
+            a.write_str(s)?;
+            a.write_str(&value)?;
+            b.write_str(s)?;
+            b.write_str(&value)?;
+        }
+        Ok(())
+    }
+
+    /// Renders a template into an [`io::Write`] sink, flushing it after every
+    /// `flush_every` segments.
+    ///
+    /// Each static and each value counts as one segment, whether or not it is
+    /// empty. Flushing periodically bounds how much output a buffered writer such as
+    /// a [`std::io::BufWriter`] holds while a very large document is produced. A
+    /// `flush_every` of `0` never flushes; data written after the last periodic flush
+    /// is left for the caller to flush.
+    ///
+    /// Returns the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error reported by `out`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let mut out = Vec::new();
+    /// let written = template.write_to_flushing(&values, &mut out, 2).unwrap();
+    /// assert_eq!(written, 13);
+    /// assert_eq!(out, b"Hello, World!");
+    /// ```
+    pub fn write_to_flushing<W: io::Write>(
+        &self,
+        flat: &FxHashMap<String, String>,
+        out: &mut W,
+        flush_every: usize,
+    ) -> io::Result<usize> {
+        let resolver = self.resolver(flat);
+        let mut written = 0;
+        let mut segments = 0;
+
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            for segment in [Cow::Borrowed(s.as_str()), resolver.get(placeholder)] {
+                out.write_all(segment.as_bytes())?;
+                written += segment.len();
+                segments += 1;
+                if flush_every > 0 && segments % flush_every == 0 {
+                    out.flush()?;
+                }
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Renders a template into owned segments tagged with where they came from.
+    ///
+    /// This is the data behind a highlighted preview: static text is tagged
+    /// [`Source::Static`] and every substituted value [`Source::Dynamic`] with the
+    /// placeholder key it was resolved from. Empty statics and the alignment slot
+    /// are skipped; a placeholder resolving to an empty value is still reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::{Source, ZipTemplate};
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hi {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "Sam".to_string());
+    ///
+    /// let segments = template.render_annotated(&values);
+    /// assert_eq!(segments[1], (Source::Dynamic { key: "name".to_string() }, "Sam".to_string()));
+    /// ```
+    pub fn render_annotated(&self, flat: &FxHashMap<String, String>) -> Vec<(Source, String)> {
+        let resolver = self.resolver(flat);
+        let mut out = Vec::with_capacity(self.statics.len() * 2);
+        let mut placeholders = self.real_placeholders().iter();
+
+        for s in self.statics.iter() {
+            if !s.is_empty() {
+                out.push((Source::Static, s.clone()));
+            }
+            if let Some(key) = placeholders.next() {
+                let value = resolver.get(key);
+                out.push((Source::Dynamic { key: key.clone() }, value.into_owned()));
+            }
+        }
+
+        out
+    }
+
+    /// Renders a template and reports which placeholders were found in the map.
+    ///
+    /// The mask has one entry per placeholder in template order (the alignment slot
+    /// is not included); an entry is `true` when the placeholder's key or one of its
+    /// `??` alternatives is in `flat`, even with an empty value. A `|default` does
+    /// not count, so `{{absent | n/a}}` renders `n/a` with a `false` entry. It is a
+    /// cheap "was all the data there" check for hot paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}{{suffix}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let (rendered, mask) = template.render_with_resolved_mask(&values);
+    /// assert_eq!(rendered, "Hello, World!");
+    /// assert_eq!(mask, [true, false]);
+    /// ```
+    pub fn render_with_resolved_mask(
+        &self,
+        flat: &FxHashMap<String, String>,
+    ) -> (String, Vec<bool>) {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let mut mask = Vec::with_capacity(self.statics.len().saturating_sub(1));
+        let mut placeholders = self.real_placeholders().iter();
+
+        for s in self.statics.iter() {
+            out.push_str(s);
+
+            if let Some(key) = placeholders.next() {
+                out.push_str(&resolver.get(key));
+                mask.push(resolver.has_key(key));
+            }
+        }
+
+        (out, mask)
+    }
+
+    /// Renders a template and reports where each substituted value ended up.
+    ///
+    /// The returned ranges are byte offsets into the *rendered* string, one per
+    /// placeholder in template order (the alignment slot is not included). A
+    /// placeholder resolving to an empty value gets an empty range positioned where
+    /// the value would have been.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let (rendered, spans) = template.render_with_output_spans(&values);
+    /// assert_eq!(&rendered[spans[0].clone()], "World");
+    /// ```
+    pub fn render_with_output_spans(
+        &self,
+        flat: &FxHashMap<String, String>,
+    ) -> (String, Vec<Range<usize>>) {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let mut spans = Vec::with_capacity(self.statics.len().saturating_sub(1));
+        let mut placeholders = self.real_placeholders().iter();
+
+        for s in self.statics.iter() {
+            out.push_str(s);
+
+            if let Some(key) = placeholders.next() {
+                let start = out.len();
+                out.push_str(&resolver.get(key));
+                spans.push(start..out.len());
+            }
+        }
+
+        (out, spans)
+    }
+
+    /// Returns the static skeleton of the template, with every placeholder removed.
+    ///
+    /// The result is the same as rendering against an empty map, but the statics are
+    /// concatenated directly without any lookups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse("Dear {{name}}, your order {{id}} shipped.");
+    /// assert_eq!(template.skeleton(), "Dear , your order  shipped.");
+    /// ```
+    pub fn skeleton(&self) -> String {
+        self.statics.concat()
+    }
+
+    /// Returns `true` if parsing the template's [`Display`](fmt::Display) output with
+    /// [`ZipTemplate::parse`] gives back the same statics and placeholders.
+    ///
+    /// Placeholder keys are compared trimmed, since parsing trims them. Templates
+    /// produced by [`ZipTemplate::parse`] are stable. Round-tripping breaks when a
+    /// static contains a delimiter that was not a tag, for example:
+    ///
+    /// - control tags kept as static text by [`ZipTemplate::parse_lenient_tags`];
+    /// - `\{{` escapes unescaped by [`TemplateConfig::brace_escapes`];
+    /// - statics or keys containing delimiters, as [`ZipTemplate::from_tokens`] and
+    ///   [`ZipTemplate::replace_static`] allow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// assert!(ZipTemplate::parse("Hello, {{ name }}!").is_roundtrip_stable());
+    /// assert!(!ZipTemplate::parse_lenient_tags("{{#if a}}{{a}}{{/if}}").is_roundtrip_stable());
+    /// ```
+    pub fn is_roundtrip_stable(&self) -> bool {
+        let reparsed = ZipTemplate::parse(&self.to_string());
+        reparsed.statics == self.statics
+            && reparsed.placeholders.len() == self.placeholders.len()
+            && reparsed
+                .placeholders
+                .iter()
+                .zip(self.placeholders.iter())
+                .all(|(reparsed, original)| reparsed == original.trim())
+    }
+
+    /// Returns a one-line dump of the parsed segments, for debugging templates that
+    /// do not render as expected.
+    ///
+    /// Each static and placeholder is listed in render order as `[static "..."]` or
+    /// `[placeholder "..."]`, quoted and escaped like [`fmt::Debug`] so whitespace
+    /// and empty segments are visible. The empty placeholder that keeps
+    /// `placeholders` as long as `statics` is listed last as `[pad ""]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse("{{greeting}}\n");
+    /// assert_eq!(
+    ///     template.debug_structure(),
+    ///     r#"[static ""] [placeholder "greeting"] [static "\n"] [pad ""]"#
+    /// );
+    /// ```
+    pub fn debug_structure(&self) -> String {
+        let last = self.placeholders.len().saturating_sub(1);
+        let mut segments = Vec::with_capacity(self.statics.len() * 2);
+
+        for (i, (s, placeholder)) in self
+            .statics
+            .iter()
+            .zip(self.placeholders.iter())
+            .enumerate()
+        {
+            segments.push(format!("[static {:?}]", s));
+            match i == last {
+                true => segments.push(format!("[pad {:?}]", placeholder)),
+                false => segments.push(format!("[placeholder {:?}]", placeholder)),
+            }
+        }
+
+        segments.join(" ")
+    }
+
+    /// Renders a template like [`ZipTemplate::render`], with `{{@dump}}` listing all
+    /// of `flat` when `dump` is `true`.
+    ///
+    /// The listing has one `key = value` line per entry, sorted by key, which shows
+    /// template authors what data a render actually receives. To inspect a JSON
+    /// document, flatten it with [`flatten_json`] first. With `dump` set to `false`,
+    /// `{{@dump}}` resolves like any other key, so a forgotten dump tag renders
+    /// nothing in production.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("<pre>{{@dump}}</pre>");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "Sam".to_string());
+    /// values.insert("id".to_string(), "7".to_string());
+    ///
+    /// assert_eq!(template.render_with_dump(&values, true), "<pre>id = 7\nname = Sam</pre>");
+    /// assert_eq!(template.render_with_dump(&values, false), "<pre></pre>");
+    /// ```
+    pub fn render_with_dump(&self, flat: &FxHashMap<String, String>, dump: bool) -> String {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+            if dump && placeholder == "@dump" {
+                let mut entries: Vec<_> = flat.iter().collect();
+                entries.sort_unstable();
+                let lines: Vec<String> = entries
+                    .into_iter()
+                    .map(|(key, value)| format!("{} = {}", key, value))
+                    .collect();
+                out.push_str(&lines.join("\n"));
+            } else {
+                out.push_str(&resolver.get(placeholder));
+            }
+        }
+
+        out
+    }
+
+    /// Renders a template, failing if any placeholder does not resolve.
+    ///
+    /// A placeholder resolves when its key (or one of its `??` alternatives) is in
+    /// `flat`, even with an empty value, or when it has a `|default`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the distinct unresolved placeholders, in order of first appearance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// assert_eq!(template.render_strict(&FxHashMap::default()), Err(vec!["name"]));
+    /// ```
+    pub fn render_strict(&self, flat: &FxHashMap<String, String>) -> Result<String, Vec<&str>> {
+        let missing = self.dry_run(flat).missing_keys;
+        if missing.is_empty() {
+            Ok(self.render(flat))
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Renders a template, failing if `flat` contains keys the template never reads.
+    ///
+    /// This is the inverse of [`ZipTemplate::render_strict`]: it catches stale data
+    /// configuration rather than missing values. A key counts as read when it is a
+    /// placeholder or one of its `??` alternatives.
+    ///
+    /// # Errors
+    ///
+    /// Returns the unreferenced keys of `flat`, sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    /// assert_eq!(template.render_strict_unused(&values).unwrap(), "Hello, World!");
+    ///
+    /// values.insert("title".to_string(), "Dr".to_string());
+    /// assert_eq!(template.render_strict_unused(&values), Err(vec!["title".to_string()]));
+    /// ```
+    pub fn render_strict_unused(
+        &self,
+        flat: &FxHashMap<String, String>,
+    ) -> Result<String, Vec<String>> {
+        let mut referenced: FxHashSet<Cow<'_, str>> = FxHashSet::default();
+        for placeholder in self.real_placeholders() {
+            referenced.insert(Cow::Borrowed(placeholder));
+            referenced.extend(tag::Tag::parse(placeholder).keys());
+        }
+
+        let mut unused: Vec<String> = flat
+            .keys()
+            .filter(|key| !referenced.contains(key.as_str()))
+            .cloned()
+            .collect();
+
+        if unused.is_empty() {
+            Ok(self.render(flat))
+        } else {
+            unused.sort_unstable();
+            Err(unused)
+        }
+    }
+
+    /// Computes the size of the rendered output and which placeholders resolve,
+    /// in a single pass and without building the output.
+    ///
+    /// Resolution follows [`ZipTemplate::render_strict`]. This is a cheap way to plan
+    /// buffer capacity and validate data before committing to a render.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}! {{missing}}");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let dry = template.dry_run(&values);
+    /// assert_eq!(dry.output_size, "Hello, World! ".len());
+    /// assert_eq!(dry.missing_keys, ["missing"]);
+    /// assert_eq!(dry.resolved_keys, 1);
+    /// ```
+    pub fn dry_run(&self, flat: &FxHashMap<String, String>) -> DryRun<'_> {
+        let resolver = self.resolver(flat);
+        let mut dry = DryRun {
+            output_size: self.statics.iter().map(String::len).sum(),
+            missing_keys: Vec::new(),
+            resolved_keys: 0,
+        };
+
+        let mut missing = FxHashSet::default();
+
+        for placeholder in self.real_placeholders() {
+            if resolver.is_resolved(placeholder) {
+                dry.resolved_keys += 1;
+                dry.output_size += resolver.get(placeholder).len();
+            } else if missing.insert(placeholder.as_str()) {
+                dry.missing_keys.push(placeholder);
+            }
+        }
+
+        dry
+    }
+
+    /// Pushes the segments of a render to `handler` instead of building a string.
+    ///
+    /// Statics and resolved values are reported in output order through
+    /// [`SegmentHandler::on_static`] and [`SegmentHandler::on_value`]. Empty statics
+    /// and the alignment slot are skipped, like in [`ZipTemplate::render_annotated`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::{SegmentHandler, ZipTemplate};
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// struct Upper(String);
+    ///
+    /// impl SegmentHandler for Upper {
+    ///     fn on_static(&mut self, text: &str) {
+    ///         self.0.push_str(text);
+    ///     }
+    ///     fn on_value(&mut self, _key: &str, value: &str) {
+    ///         self.0.push_str(&value.to_uppercase());
+    ///     }
+    /// }
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let mut handler = Upper(String::new());
+    /// template.render_events(&values, &mut handler);
+    /// assert_eq!(handler.0, "Hello, WORLD!");
+    /// ```
+    pub fn render_events<H: SegmentHandler>(
+        &self,
+        flat: &FxHashMap<String, String>,
+        handler: &mut H,
+    ) {
+        let resolver = self.resolver(flat);
+        let mut placeholders = self.real_placeholders().iter();
+
+        for s in self.statics.iter() {
+            if !s.is_empty() {
+                handler.on_static(s);
+            }
+            if let Some(key) = placeholders.next() {
+                handler.on_value(key, &resolver.get(key));
+            }
+        }
+    }
+
+    /// Renders a template, calling `hooks` around every placeholder substitution.
+    ///
+    /// [`RenderHooks::before_placeholder`] and [`RenderHooks::after_placeholder`] are
+    /// called in template order for each placeholder (the alignment slot excluded),
+    /// so a tracing layer can open and close a span per resolution.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    /// use zip_templates::{RenderHooks, ZipTemplate};
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// #[derive(Default)]
+    /// struct Spans(Vec<(String, Instant)>);
+    ///
+    /// impl RenderHooks for Spans {
+    ///     fn before_placeholder(&mut self, key: &str) {
+    ///         self.0.push((key.to_string(), Instant::now()));
+    ///     }
+    ///     fn after_placeholder(&mut self, _key: &str, _value: &str) {}
+    /// }
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut spans = Spans::default();
+    /// assert_eq!(template.render_instrumented(&FxHashMap::default(), &mut spans), "Hello, !");
+    /// assert_eq!(spans.0[0].0, "name");
+    /// ```
+    pub fn render_instrumented<H: RenderHooks>(
+        &self,
+        flat: &FxHashMap<String, String>,
+        hooks: &mut H,
+    ) -> String {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let mut placeholders = self.real_placeholders().iter();
+
+        for s in self.statics.iter() {
+            out.push_str(s);
+
+            if let Some(key) = placeholders.next() {
+                hooks.before_placeholder(key);
+                let value = resolver.get(key);
+                out.push_str(&value);
+                hooks.after_placeholder(key, &value);
+            }
+        }
+
+        out
+    }
+
+    /// Computes a stable 64-bit hash of the rendered output without building it.
+    ///
+    /// The segments are streamed through FNV-1a byte by byte, so the hash depends
+    /// only on the output text and not on how it splits into statics and values:
+    /// two renders with identical output always hash equal, across processes and
+    /// releases. Suitable for ETags and cache keys, not for security.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let other = ZipTemplate::parse("Hello, World!");
+    /// assert_eq!(template.render_hash(&values), other.render_hash(&values));
+    /// ```
+    pub fn render_hash(&self, flat: &FxHashMap<String, String>) -> u64 {
+        let resolver = self.resolver(flat);
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let feed = |hash: u64, bytes: &[u8]| {
+            bytes
+                .iter()
+                .fold(hash, |hash, &b| (hash ^ b as u64).wrapping_mul(FNV_PRIME))
+        };
+
+        let dynamics = self
+            .placeholders
+            .iter()
+            .map(|placeholder| resolver.get(placeholder));
+
+        self.statics
+            .iter()
+            .zip(dynamics)
+            .fold(FNV_OFFSET, |hash, (s, dynamic)| {
+                feed(feed(hash, s.as_bytes()), dynamic.as_bytes())
+            })
+    }
+
+    /// Renders a template, dropping the statics around empty `{{?key}}` placeholders.
+    ///
+    /// The statics conditional on `{{?key}}` are exactly two: the static immediately
+    /// before the tag and the static immediately after it. When `key` resolves to an
+    /// empty value both are omitted from the output; otherwise both are kept. A static
+    /// between two conditional placeholders is omitted if either of them is empty.
+    /// Since whole statics are dropped, the text around a conditional tag should be
+    /// only the punctuation that belongs to it.
+    ///
+    /// [`ZipTemplate::render`] resolves `{{?key}}` like `{{key}}` and keeps all statics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("{{name}} ({{?nickname}})");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "Samuel".to_string());
+    /// assert_eq!(template.render_conditional(&values), "Samuel");
+    ///
+    /// values.insert("nickname".to_string(), "Sam".to_string());
+    /// assert_eq!(template.render_conditional(&values), "Samuel (Sam)");
+    /// ```
+    pub fn render_conditional(&self, flat: &FxHashMap<String, String>) -> String {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let values: Vec<(Cow<'_, str>, bool)> = self
+            .placeholders
+            .iter()
+            .map(|placeholder| {
+                let value = resolver.get(placeholder);
+                let suppress = value.is_empty()
+                    && placeholder.starts_with('?')
+                    && tag::Tag::parse(placeholder).is_conditional();
+                (value, suppress)
+            })
+            .collect();
+
+        for (i, s) in self.statics.iter().enumerate() {
+            let suppressed_by_previous = i > 0 && values[i - 1].1;
+            let suppressed_by_next = values.get(i).is_some_and(|(_, suppress)| *suppress);
+            if !suppressed_by_previous && !suppressed_by_next {
+                out.push_str(s);
+            }
+            if let Some((value, _)) = values.get(i) {
+                out.push_str(value);
+            }
+        }
+
+        out
+    }
+
+    /// Renders a template, collapsing every run of whitespace in the statics to a
+    /// single space. Values are emitted unchanged, whitespace included.
+    ///
+    /// This is a naive minifier working in the same pass as the render: it knows
+    /// nothing about HTML, so whitespace inside `<pre>`, `<textarea>` or inline
+    /// scripts in the template text is collapsed too. Runs are collapsed within each
+    /// static; whitespace at the edge of a static is kept as one space even when the
+    /// neighbouring value starts or ends with whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("<p>\n    {{body}}\n</p>");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("body".to_string(), "a  b".to_string());
+    ///
+    /// assert_eq!(template.render_minified(&values), "<p> a  b </p>");
+    /// ```
+    pub fn render_minified(&self, flat: &FxHashMap<String, String>) -> String {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            let mut in_whitespace = false;
+            for c in s.chars() {
+                match c.is_whitespace() {
+                    true if in_whitespace => {}
+                    true => out.push(' '),
+                    false => out.push(c),
+                }
+                in_whitespace = c.is_whitespace();
+            }
+            out.push_str(&resolver.get(placeholder));
+        }
+
+        out
+    }
+
+    /// Renders a template, expanding a placeholder that names an array to all of its
+    /// elements joined by `separator`.
+    ///
+    /// A key `tags` with no `tags` entry in `flat` but with `tags.0`, `tags.1`, ...
+    /// entries, as produced by [`flatten_json`] for an array, resolves to those
+    /// values in index order up to the first missing index. This applies to every
+    /// key of a `??` chain, and a `|default` is only used when neither the key nor
+    /// its indexed entries are present. Pass `""` to join without a separator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use zip_templates::{flatten_json, ZipTemplate};
+    ///
+    /// let flat = flatten_json(&json!({ "tags": ["rust", "web"] }));
+    /// let template = ZipTemplate::parse("tags: {{tags}}");
+    /// assert_eq!(template.render_splat(&flat, ", "), "tags: rust, web");
+    /// ```
+    pub fn render_splat(&self, flat: &FxHashMap<String, String>, separator: &str) -> String {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        let splat = |key: &str| {
+            let items: Vec<&str> = (0..)
+                .map_while(|i| flat.get(&format!("{}.{}", key, i)))
+                .map(String::as_str)
+                .collect();
+            (!items.is_empty()).then(|| Cow::Owned(items.join(separator)))
+        };
+
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+            if !placeholder.is_empty() {
+                out.push_str(&resolver.get_or(placeholder, splat));
+            }
+        }
+
+        out
+    }
+
+    /// Renders a template, escaping `{{` and `}}` in values as `\{{` and `\}}`.
+    ///
+    /// Output that is stored and later parsed again as a template, with
+    /// [`TemplateConfig::brace_escapes`] enabled, then gives back the values as
+    /// literal text instead of turning a value like `{{secret}}` into a placeholder.
+    /// Statics are trusted and emitted unchanged, but a `{{` formed across the
+    /// boundary between a value and its neighbour is escaped too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::{TemplateConfig, ZipTemplate};
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "{{admin}}".to_string());
+    ///
+    /// let rendered = template.render_escaping_braces(&values);
+    /// assert_eq!(rendered, r"Hello, \{{admin\}}!");
+    ///
+    /// let reparsed = TemplateConfig::new().brace_escapes(true).parse(&rendered).unwrap();
+    /// assert_eq!(reparsed.statics, ["Hello, {{admin}}!"]);
+    /// ```
+    pub fn render_escaping_braces(&self, flat: &FxHashMap<String, String>) -> String {
+        let resolver = self.resolver(flat);
+        fn push(out: &mut String, mut text: &str, escape_braces: bool) {
+            // A `{` ending the output and one starting `text` would form an opener.
+            if out.ends_with('{') && text.starts_with('{') {
+                out.pop();
+                out.push_str("\\{{");
+                text = &text[1..];
+            }
+            if escape_braces && text.contains(['{', '}']) {
+                out.push_str(&text.replace("{{", "\\{{").replace("}}", "\\}}"));
+            } else {
+                out.push_str(text);
+            }
+        }
+
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            push(&mut out, s, false);
+            push(&mut out, &resolver.get(placeholder), true);
+        }
+
+        out
+    }
+
+    /// Renders a template, passing each static segment through `f` before it is
+    /// emitted. Values are emitted unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("<DIV>{{body}}</DIV>");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("body".to_string(), "KEEP".to_string());
+    ///
+    /// let rendered = template.render_transform_statics(&values, |s| Cow::Owned(s.to_lowercase()));
+    /// assert_eq!(rendered, "<div>KEEP</div>");
+    /// ```
+    pub fn render_transform_statics<F>(&self, flat: &FxHashMap<String, String>, f: F) -> String
+    where
+        F: Fn(&str) -> Cow<'_, str>,
+    {
+        let resolver = self.resolver(flat);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(&f(s));
+            out.push_str(&resolver.get(placeholder));
+        }
+
+        out
+    }
+
+    /// Renders a template by interleaving the stored static segments with the provided
+    /// dynamic values.
+    ///
+    /// This function iterates through the internal static parts of the template and
+    /// appends a dynamic value after each part, consuming the `dynamics` slice strictly
+    /// in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `dynamics` - A slice of strings to insert between static segments.
+    ///
+    /// # Returns
+    ///
+    /// A new `String` containing the rendered content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse(
+    ///    "Hello {{name}}!"
+    /// );
+    ///
+    /// let args = vec!["World".to_string()];
+    /// let result = template.render_from_vec(&args);
+    ///
+    /// assert_eq!(result, "Hello World!");
+    /// ```
+    pub fn render_from_vec(&self, dynamics: &[String]) -> String {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        let mut dynamics_iter = dynamics.iter();
+
+        for s in self.statics.iter() {
+            out.push_str(s);
+
+            if let Some(dynamic) = dynamics_iter.next() {
+                out.push_str(dynamic);
+            }
+        }
+
+        out
+    }
+
+    /// Renders a template from values supplied in the caller's order rather than the
+    /// template's.
+    ///
+    /// `dynamics[i]` is the value of the placeholder named `key_order[i]`; the output
+    /// follows the template's order as usual. Placeholders missing from `key_order`
+    /// resolve like missing keys in [`ZipTemplate::render`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`OrderLengthError`] if `dynamics` and `key_order` have different
+    /// lengths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse("{{first}} {{last}}");
+    /// let rendered = template.render_from_vec_ordered(&["Doe", "Jane"], &["last", "first"]);
+    /// assert_eq!(rendered.unwrap(), "Jane Doe");
+    /// ```
+    pub fn render_from_vec_ordered<S: AsRef<str>>(
+        &self,
+        dynamics: &[S],
+        key_order: &[&str],
+    ) -> Result<String, OrderLengthError> {
+        if dynamics.len() != key_order.len() {
+            return Err(OrderLengthError {
+                values: dynamics.len(),
+                keys: key_order.len(),
+            });
+        }
+
+        let values: FxHashMap<String, &str> = key_order
+            .iter()
+            .zip(dynamics)
+            .map(|(key, value)| (key.to_string(), value.as_ref()))
+            .collect();
+
+        let resolver = self.resolver(&values);
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+            out.push_str(&resolver.get(placeholder));
+        }
+        Ok(out)
+    }
+
+    /// Arranges `values` in the order of the template's placeholders, ready for
+    /// [`ZipTemplate::render_from_vec`]. See also the [`values_for!`] macro.
+    ///
+    /// `values` pairs placeholder keys with their values, in any order. A key
+    /// used several times fills each of its slots.
+    ///
+    /// # Errors
+    ///
+    /// Returns the distinct placeholders without a value, in order of first
+    /// appearance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse("{{age}} years, {{name}}");
+    /// let values = [("name", "Sam".to_string()), ("age", "30".to_string())];
+    /// let dynamics = template.values_in_order(&values).unwrap();
+    /// assert_eq!(template.render_from_vec(&dynamics), "30 years, Sam");
+    ///
+    /// assert_eq!(template.values_in_order(&values[..1]), Err(vec!["age"]));
+    /// ```
+    pub fn values_in_order(&self, values: &[(&str, String)]) -> Result<Vec<String>, Vec<&str>> {
+        let by_key: FxHashMap<&str, &String> = values.iter().map(|(k, v)| (*k, v)).collect();
+        let mut missing = Vec::new();
+
+        let ordered = self
+            .real_placeholders()
+            .iter()
+            .filter_map(|key| match by_key.get(key.as_str()) {
+                Some(value) => Some((*value).clone()),
+                None => {
+                    if !missing.contains(&key.as_str()) {
+                        missing.push(key.as_str());
+                    }
+                    None
+                }
+            })
+            .collect();
+
+        match missing.is_empty() {
+            true => Ok(ordered),
+            false => Err(missing),
+        }
+    }
+}
+
+/// Builds the values of a template's placeholders in placeholder order, for
+/// [`ZipTemplate::render_from_vec`].
+///
+/// Each value is converted with [`ToString`]. Expands to
+/// [`ZipTemplate::values_in_order`], so it evaluates to a `Result` that lists the
+/// placeholders left without a value.
+///
+/// # Examples
+///
+/// ```
+/// use zip_templates::{values_for, ZipTemplate};
+///
+/// let template = ZipTemplate::parse("{{user.name}} is {{age}}");
+/// let (name, age) = ("Sam", 30);
+/// let dynamics = values_for!(template, { "age" => age, "user.name" => name }).unwrap();
+/// assert_eq!(template.render_from_vec(&dynamics), "Sam is 30");
+/// ```
+#[macro_export]
+macro_rules! values_for {
+    ($template:expr, { $($key:expr => $value:expr),* $(,)? }) => {
+        $template.values_in_order(&[$(($key, ::std::string::ToString::to_string(&$value))),*])
+    };
+}
+
+/// Resolves a placeholder against `flat`, defaulting to an empty string.
+///
+/// See [`resolve`] for the rules.
+pub(crate) fn lookup<'a, V: AsRef<str>>(
+    flat: &'a FxHashMap<String, V>,
+    placeholder: &'a str,
+) -> Cow<'a, str> {
+    resolve(placeholder, |key| {
+        flat.get(key).map(|value| Cow::Borrowed(value.as_ref()))
+    })
+}
+
+/// Resolves a placeholder through `get`, which returns the value of a single key,
+/// defaulting to an empty string.
+///
+/// An exact match always wins, so plain keys never pay for the tag syntax. Only
+/// when the placeholder is missing is it split into its parts (see [`tag`]): the
+/// first key of its `??` chain with a non-empty value is used, then its `|default`,
+/// and the `|>` filters are applied to the result. Format specs are ignored here.
+pub(crate) fn resolve<'a>(
+    placeholder: &'a str,
+    get: impl Fn(&str) -> Option<Cow<'a, str>>,
+) -> Cow<'a, str> {
+    if let Some(value) = get(placeholder) {
+        return value;
+    }
+    if !placeholder.contains(['?', '|', ':', '\\']) {
+        return Cow::Borrowed("");
+    }
+
+    let tag = tag::Tag::parse(placeholder);
+    let value = tag
+        .keys()
+        .filter_map(|key| get(&key))
+        .find(|value| !value.is_empty())
+        .or_else(|| tag.default.map(tag::unescape))
+        .unwrap_or(Cow::Borrowed(""));
+    tag.apply_filters(value)
+}
+
+/// Follows the dot-separated `path` through objects and arrays. An empty path is
+/// `data` itself.
+fn json_path<'a>(data: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(data);
+    }
+    path.split('.')
+        .try_fold(data, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+/// Renders the scalar at `path` like [`flatten_json_visit`] does; objects, arrays
+/// and missing paths give `None`.
+fn json_scalar<'a>(data: &'a Value, path: &str) -> Option<Cow<'a, str>> {
+    match json_path(data, path)? {
+        Value::Object(_) | Value::Array(_) => None,
+        Value::Null => Some(Cow::Borrowed("")),
+        Value::String(s) => Some(Cow::Borrowed(s)),
+        value => Some(Cow::Owned(value.to_string())),
+    }
+}
+
+/// Resolves a placeholder in `data` with the same tag rules as [`lookup`].
+fn json_lookup<'a>(data: &'a Value, placeholder: &'a str) -> Cow<'a, str> {
+    match json_lookup_with(data, placeholder, ObjectPolicy::Empty) {
+        Ok(value) => value,
+        Err(_) => unreachable!("only ObjectPolicy::Error fails"),
+    }
+}
+
+/// Resolves a placeholder in `data` like [`json_lookup`], rendering objects and
+/// arrays according to `policy`. Under [`ObjectPolicy::Error`] the key that led
+/// to one is returned as the error.
+fn json_lookup_with<'a>(
+    data: &'a Value,
+    placeholder: &'a str,
+    policy: ObjectPolicy,
+) -> Result<Cow<'a, str>, String> {
+    fn first_scalar(value: &Value) -> Option<&Value> {
+        match value {
+            Value::Object(map) => first_scalar(map.values().next()?),
+            Value::Array(items) => first_scalar(items.first()?),
+            scalar => Some(scalar),
+        }
+    }
+
+    // An empty path makes `json_scalar` render the value it is given.
+    let resolve = |key: &str| -> Result<Option<Cow<'a, str>>, String> {
+        let value = match json_path(data, key) {
+            Some(value @ (Value::Object(_) | Value::Array(_))) => match policy {
+                ObjectPolicy::Empty => Cow::Borrowed(""),
+                ObjectPolicy::Json => Cow::Owned(value.to_string()),
+                ObjectPolicy::FirstField => first_scalar(value)
+                    .and_then(|scalar| json_scalar(scalar, ""))
+                    .unwrap_or(Cow::Borrowed("")),
+                ObjectPolicy::Error => return Err(key.to_string()),
+            },
+            Some(scalar) => json_scalar(scalar, "").unwrap_or(Cow::Borrowed("")),
+            None => return Ok(None),
+        };
+        Ok(Some(value))
+    };
+
+    if let Some(value) = resolve(placeholder)? {
+        return Ok(value);
+    }
+    if !placeholder.contains(['?', '|', ':', '\\']) {
+        return Ok(Cow::Borrowed(""));
+    }
+
+    let tag = tag::Tag::parse(placeholder);
+    for key in tag.keys() {
+        if let Some(value) = resolve(&key)? {
+            if !value.is_empty() {
+                return Ok(tag.apply_filters(value));
+            }
+        }
+    }
+    let value = tag.default.map(tag::unescape).unwrap_or(Cow::Borrowed(""));
+    Ok(tag.apply_filters(value))
+}
+
+/// Returns `true` if `placeholder` resolves, with `contains` telling whether a
+/// single key is present: its key, one of its `??` alternatives, or its `|default`
+/// is present. Present-but-empty values count.
+pub(crate) fn is_resolved_with(placeholder: &str, contains: impl Fn(&str) -> bool) -> bool {
+    if contains(placeholder) {
+        return true;
+    }
+    if !placeholder.contains(['?', '|', ':', '\\']) {
+        return false;
+    }
+
+    let tag = tag::Tag::parse(placeholder);
+    tag.default.is_some() || tag.keys().any(|key| contains(&key))
+}
+
+/// Flattens a nested JSON object into a flat map with dot-separated keys.
+///
+/// This function recursively traverses a `serde_json::Value`. Nested object keys are
+/// joined by dots (`parent.child`), and array indices are treated as keys (`array.0`).
+/// Primitive values are converted to strings.
+///
+/// # Arguments
+///
+/// * `value` - A reference to the `serde_json::Value` to flatten.
+///
+/// # Returns
+///
+/// A `FxHashMap<String, String>` where keys represent the path to the value and
+/// values are the string representations of the leaf nodes.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use rustc_hash::FxHashMap;
+/// # // Mock function definition for the doctest if strictly necessary,
+/// # // or assume the user imports it from the crate.
+/// # use serde_json::Value;
+/// # fn flatten_json(value: &Value) -> FxHashMap<String, String> {
+/// #    // ... implementation ...
+/// #    // (Mocking body for brevity in display, actual test runs real code)
+/// #    let mut out = FxHashMap::default();
+/// #    // simplified mock logic for the example's sake:
+/// #    if value.is_object() {
+/// #        out.insert("user.name".to_string(), "Alice".to_string());
+/// #        out.insert("user.tags.0".to_string(), "admin".to_string());
+/// #        out.insert("active".to_string(), "true".to_string());
+/// #    }
+/// #    out
+/// # }
+///
+/// let data = json!({
+///     "user": {
+///         "name": "Alice",
+///         "tags": ["admin"]
+///     },
+///     "active": true
+/// });
+///
+/// let flattened = flatten_json(&data);
+///
+/// assert_eq!(flattened.get("user.name"), Some(&"Alice".to_string()));
+/// assert_eq!(flattened.get("user.tags.0"), Some(&"admin".to_string()));
+/// assert_eq!(flattened.get("active"), Some(&"true".to_string()));
+/// ```
+pub fn flatten_json(value: &Value) -> FxHashMap<String, String> {
+    let mut out = FxHashMap::default();
+    flatten_json_visit(value, |key, value| {
+        out.insert(key.to_string(), value.to_string());
+    });
+    out
+}
+
+/// Walks a nested JSON value, calling `visit` with the dot-separated key and string
+/// value of every leaf, without building a map.
+///
+/// Keys and values are exactly those [`flatten_json`] would produce, in document
+/// order. This lets callers filter or route pairs as they are found, for example
+/// keeping only the keys a template needs out of a very large document.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+///
+/// let data = json!({ "user": { "name": "Alice", "tags": ["admin"] }, "active": true });
+/// let mut pairs = Vec::new();
+/// zip_templates::flatten_json_visit(&data, |key, value| {
+///     if key.starts_with("user.") {
+///         pairs.push(format!("{}={}", key, value));
+///     }
+/// });
+///
+/// assert_eq!(pairs, ["user.name=Alice", "user.tags.0=admin"]);
+/// ```
+pub fn flatten_json_visit<F: FnMut(&str, &str)>(value: &Value, mut visit: F) {
+    fn helper<F: FnMut(&str, &str)>(value: &Value, key: &mut String, visit: &mut F) {
+        let push_segment = |key: &mut String, segment: &str| {
+            if !key.is_empty() {
+                key.push('.');
+            }
+            key.push_str(segment);
+        };
+
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    let len = key.len();
+                    push_segment(key, k);
+                    helper(v, key, visit);
+                    key.truncate(len);
+                }
+            }
+            Value::Array(arr) => {
+                for (i, v) in arr.iter().enumerate() {
+                    let len = key.len();
+                    push_segment(key, &i.to_string());
+                    helper(v, key, visit);
+                    key.truncate(len);
+                }
+            }
+            Value::Null => visit(key, ""),
+            _ => visit(key, value.to_string().trim_matches('"')),
+        }
+    }
+    helper(value, &mut String::new(), &mut visit);
+}
+
+/// Flattens a nested JSON object like [`flatten_json`], borrowing string leaves.
+///
+/// String leaves are returned as `Cow::Borrowed` slices into `value`, without
+/// escaping; only numbers, booleans and nulls are formatted into owned strings.
+/// Keys are always allocated since they are built from the path.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+/// use serde_json::json;
+///
+/// let data = json!({ "user": { "name": "Alice", "age": 30 } });
+/// let flattened = zip_templates::flatten_json_borrowed(&data);
+///
+/// assert!(matches!(flattened["user.name"], Cow::Borrowed("Alice")));
+/// assert_eq!(flattened["user.age"], "30");
+/// ```
+pub fn flatten_json_borrowed(value: &Value) -> FxHashMap<String, Cow<'_, str>> {
+    fn helper<'a>(value: &'a Value, prefix: String, out: &mut FxHashMap<String, Cow<'a, str>>) {
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    let new_prefix = if prefix.is_empty() {
+                        k.clone()
+                    } else {
+                        format!("{}.{}", prefix, k)
+                    };
+                    helper(v, new_prefix, out);
+                }
+            }
+            Value::Array(arr) => {
+                for (i, v) in arr.iter().enumerate() {
+                    let new_prefix = if prefix.is_empty() {
+                        i.to_string()
+                    } else {
+                        format!("{}.{}", prefix, i)
+                    };
+                    helper(v, new_prefix, out);
+                }
+            }
+            Value::Null => {
+                out.insert(prefix, Cow::Borrowed(""));
+            }
+            Value::String(s) => {
+                out.insert(prefix, Cow::Borrowed(s));
+            }
+            _ => {
+                out.insert(prefix, Cow::Owned(value.to_string()));
+            }
+        }
+    }
+    let mut out = FxHashMap::default();
+    helper(value, String::new(), &mut out);
+    out
+}
+
+// (render moved into impl ZipTemplate)
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_parse_render_flat() {
+        let tpl = "Hi, {{user.name.first}} — balance: {{account.balance}} USD";
+        let parsed = ZipTemplate::parse(tpl);
+        let mut flat = FxHashMap::default();
+        flat.insert("user.name.first".to_string(), "Sam".to_string());
+        flat.insert("account.balance".to_string(), "12.34".to_string());
+        let out = parsed.render(&flat);
+        assert_eq!(out, "Hi, Sam — balance: 12.34 USD");
+    }
+
+    #[test]
+    fn missing_key_non_strict() {
+        let tpl = "Hello, {{name}}!";
+        let parsed = ZipTemplate::parse(tpl);
+        let flat = FxHashMap::default();
+        let out = parsed.render(&flat);
+        assert_eq!(out, "Hello, !");
+    }
+
+    #[test]
+    fn multiple_placeholders() {
+        let tpl = "{{a}},{{b}},{{c}}";
+        let parsed = ZipTemplate::parse(tpl);
+        let mut flat = FxHashMap::default();
+        flat.insert("a".to_string(), "1".to_string());
+        flat.insert("b".to_string(), "2".to_string());
+        flat.insert("c".to_string(), "3".to_string());
+        let out = parsed.render(&flat);
+        assert_eq!(out, "1,2,3");
+    }
+
+    #[test]
+    fn empty_template() {
+        let tpl = "";
+        let parsed = ZipTemplate::parse(tpl);
+        let flat = FxHashMap::default();
+        let out = parsed.render(&flat);
+        assert_eq!(out, "");
+    }
+
+    #[test]
+    fn only_static() {
+        let tpl = "static text only";
+        let parsed = ZipTemplate::parse(tpl);
+        let flat = FxHashMap::default();
+        let out = parsed.render(&flat);
+        assert_eq!(out, "static text only");
+    }
+
+    #[test]
+    fn basic_parse_render_flat_from_vec() {
+        let tpl = "Hi, {{user.name.first}} — balance: {{account.balance}} USD";
+        let parsed = ZipTemplate::parse(tpl);
+        let out = parsed.render_from_vec(&["Sam".to_string(), "12.34".to_string()]);
+        assert_eq!(out, "Hi, Sam — balance: 12.34 USD");
+    }
+
+    #[test]
+    fn missing_key_non_strict_from_vec() {
+        let tpl = "Hello, {{name}}!";
+        let parsed = ZipTemplate::parse(tpl);
+        let out = parsed.render_from_vec(&Vec::new());
+        assert_eq!(out, "Hello, !");
+    }
+
+    #[test]
+    fn multiple_placeholders_from_vec() {
+        let tpl = "{{a}},{{b}},{{c}}";
+        let parsed = ZipTemplate::parse(tpl);
+        let mut flat = FxHashMap::default();
+        flat.insert("a".to_string(), "1".to_string());
+        flat.insert("b".to_string(), "2".to_string());
+        flat.insert("c".to_string(), "3".to_string());
+        let out = parsed.render_from_vec(&["1".to_string(), "2".to_string(), "3".to_string()]);
+        assert_eq!(out, "1,2,3");
+    }
+
+    #[test]
+    fn empty_template_from_vec() {
+        let tpl = "";
+        let parsed = ZipTemplate::parse(tpl);
+        let out = parsed.render_from_vec(&Vec::new());
+        assert_eq!(out, "");
+    }
+
+    #[test]
+    fn only_static_from_vec() {
+        let tpl = "static text only";
+        let parsed = ZipTemplate::parse(tpl);
+        let out = parsed.render_from_vec(&[]);
+        assert_eq!(out, "static text only");
+    }
+
+    #[test]
+    fn render_annotated_tags_sources() {
+        let parsed = ZipTemplate::parse("Hi {{name}}!");
+        let mut flat = FxHashMap::default();
+        flat.insert("name".to_string(), "Sam".to_string());
+        let out = parsed.render_annotated(&flat);
+        assert_eq!(
+            out,
+            vec![
+                (Source::Static, "Hi ".to_string()),
+                (
+                    Source::Dynamic {
+                        key: "name".to_string()
+                    },
+                    "Sam".to_string()
+                ),
+                (Source::Static, "!".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn output_spans_slice_values() {
+        let parsed = ZipTemplate::parse("{{a}} and {{missing}}, {{b}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("a".to_string(), "first".to_string());
+        flat.insert("b".to_string(), "ünïcode".to_string());
+        let (out, spans) = parsed.render_with_output_spans(&flat);
+        assert_eq!(out, "first and , ünïcode");
+        assert_eq!(spans.len(), 3);
+        assert_eq!(&out[spans[0].clone()], "first");
+        assert_eq!(spans[1], 10..10);
+        assert_eq!(&out[spans[2].clone()], "ünïcode");
+    }
+
+    #[test]
+    fn skeleton_matches_empty_render() {
+        let parsed =
+            ZipTemplate::parse("Hi, {{user.name.first}} — balance: {{account.balance}} USD");
+        assert_eq!(parsed.skeleton(), parsed.render(&FxHashMap::default()));
+        assert_eq!(parsed.skeleton(), "Hi,  — balance:  USD");
+    }
+
+    #[test]
+    fn replace_middle_static() {
+        let mut parsed = ZipTemplate::parse("<{{a}}|{{b}}>");
+        assert_eq!(parsed.static_parts_count(), 3);
+        parsed.replace_static(1, " - ").unwrap();
+        let mut flat = FxHashMap::default();
+        flat.insert("a".to_string(), "1".to_string());
+        flat.insert("b".to_string(), "2".to_string());
+        assert_eq!(parsed.render(&flat), "<1 - 2>");
+        assert_eq!(
+            parsed.replace_static(3, "x"),
+            Err(IndexError { index: 3, len: 3 })
+        );
+    }
+
+    #[test]
+    fn limits_key_too_long() {
+        let err = ZipTemplate::parse_with_limits("{{a}} {{ abcdef }}", 5, 10).unwrap_err();
+        assert_eq!(
+            err,
+            LimitError::KeyTooLong {
+                index: 1,
+                len: 6,
+                max: 5
+            }
+        );
+    }
+
+    #[test]
+    fn limits_too_many_placeholders() {
+        let tpl = "{{a}}".repeat(1000);
+        let err = ZipTemplate::parse_with_limits(&tpl, 5, 3).unwrap_err();
+        assert_eq!(err, LimitError::TooManyPlaceholders { max: 3 });
+    }
+
+    #[test]
+    fn limits_just_under_both() {
+        let tpl = "{{abcde}}-{{ab}}-{{abc}}";
+        let parsed = ZipTemplate::parse_with_limits(tpl, 5, 3).unwrap();
+        let plain = ZipTemplate::parse(tpl);
+        assert_eq!(parsed.statics, plain.statics);
+        assert_eq!(parsed.placeholders, plain.placeholders);
+    }
+
+    #[test]
+    fn render_stream_is_lazy() {
+        let parsed = ZipTemplate::parse("row {{i}}: {{name}}");
+        let mut produced = 0;
+        let rows = std::iter::from_fn(|| {
+            produced += 1;
+            let mut row = FxHashMap::default();
+            row.insert("i".to_string(), produced.to_string());
+            row.insert("name".to_string(), format!("item{}", produced));
+            Some(row)
+        });
+
+        let out: Vec<String> = parsed.render_stream(rows).take(2).collect();
+        assert_eq!(out, ["row 1: item1", "row 2: item2"]);
+        assert_eq!(produced, 2);
+    }
+
+    #[test]
+    fn coalesce_secondary_supplies_value() {
+        let parsed = ZipTemplate::parse("Hello, {{nickname ?? name}}!");
+        let mut flat = FxHashMap::default();
+        flat.insert("name".to_string(), "Samuel".to_string());
+        assert_eq!(parsed.render(&flat), "Hello, Samuel!");
+
+        flat.insert("nickname".to_string(), String::new());
+        assert_eq!(parsed.render(&flat), "Hello, Samuel!");
+
+        flat.insert("nickname".to_string(), "Sam".to_string());
+        assert_eq!(parsed.render(&flat), "Hello, Sam!");
+    }
+
+    #[test]
+    fn coalesce_chain_and_all_missing() {
+        let parsed = ZipTemplate::parse("[{{a ?? b ?? c}}]");
+        let mut flat = FxHashMap::default();
+        assert_eq!(parsed.render(&flat), "[]");
+
+        flat.insert("c".to_string(), "third".to_string());
+        assert_eq!(parsed.render(&flat), "[third]");
+    }
+
+    #[test]
+    fn render_with_timing_records_slow_resolver() {
+        let parsed = ZipTemplate::parse("{{fast}} {{slow}} {{missing}}");
+        let (out, timings) = parsed.render_with_timing(|key| match key {
+            "fast" => Some("f".to_string()),
+            "slow" => {
+                std::thread::sleep(Duration::from_millis(20));
+                Some("s".to_string())
+            }
+            _ => None,
+        });
+
+        assert_eq!(out, "f s ");
+        let keys: Vec<&str> = timings.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["fast", "slow", "missing"]);
+        assert!(timings[1].1 >= Duration::from_millis(20));
+        assert!(timings[1].1 > timings[0].1);
+        assert!(timings[1].1 > timings[2].1);
+    }
+
+    #[test]
+    fn renders_same_ignores_unused_keys() {
+        let parsed = ZipTemplate::parse("{{a}} and {{b}}");
+        let mut before = FxHashMap::default();
+        before.insert("a".to_string(), "1".to_string());
+        before.insert("b".to_string(), "2".to_string());
+        before.insert("unused".to_string(), "x".to_string());
+
+        let mut after = before.clone();
+        after.insert("unused".to_string(), "y".to_string());
+        assert!(parsed.renders_same(&before, &after));
+
+        after.insert("b".to_string(), "3".to_string());
+        assert!(!parsed.renders_same(&before, &after));
+    }
+
+    #[test]
+    fn try_parse_rejects_degenerate_placeholders() {
+        for (tpl, span) in [
+            ("a {{   }} b", 2..9),
+            ("a {{.}} b", 2..7),
+            ("{{ok}}{{..}}", 6..12),
+        ] {
+            assert_eq!(
+                ZipTemplate::try_parse(tpl).unwrap_err(),
+                ParseError::DegeneratePlaceholder { span }
+            );
+        }
+    }
+
+    #[test]
+    fn try_parse_accepts_regular_keys() {
+        let parsed = ZipTemplate::try_parse("{{a}} and {{ user.name }}").unwrap();
+        assert_eq!(parsed.placeholders, ["a", "user.name", ""]);
+
+        // The lenient parser keeps degenerate keys as-is.
+        let lenient = ZipTemplate::parse("{{..}}");
+        assert_eq!(lenient.placeholders, ["..", ""]);
+    }
+
+    #[test]
+    fn flatten_borrowed_borrows_string_leaves() {
+        let data = serde_json::json!({
+            "user": { "name": "Alice", "tags": ["admin"] },
+            "active": true,
+            "score": 1.5,
+            "missing": null
+        });
+        let flat = flatten_json_borrowed(&data);
+
+        match &flat["user.name"] {
+            Cow::Borrowed(s) => assert!(std::ptr::eq(
+                s.as_ptr(),
+                data["user"]["name"].as_str().unwrap().as_ptr()
+            )),
+            Cow::Owned(_) => panic!("string leaf was copied"),
+        }
+        assert!(matches!(flat["user.tags.0"], Cow::Borrowed("admin")));
+        assert!(matches!(flat["active"], Cow::Owned(_)));
+        assert_eq!(flat["active"], "true");
+        assert_eq!(flat["score"], "1.5");
+        assert_eq!(flat["missing"], "");
+
+        let parsed = ZipTemplate::parse("{{user.name}} ({{user.tags.0}}): {{score}}");
+        assert_eq!(parsed.render_borrowed(&flat), "Alice (admin): 1.5");
+        assert_eq!(
+            parsed.render_borrowed(&flat),
+            parsed.render(&flatten_json(&data))
+        );
+    }
+
+    #[test]
+    fn render_rows_across_parallel_columns() {
+        let parsed =
+            ZipTemplate::parse("<tr><td>{{id}}</td><td>{{name}}</td><td>{{qty}}</td></tr>");
+        let column = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let mut columns = FxHashMap::default();
+        columns.insert("id".to_string(), column(&["1", "2"]));
+        columns.insert("name".to_string(), column(&["apple", "pear"]));
+        columns.insert("qty".to_string(), column(&["3", "5"]));
+        columns.insert("unused".to_string(), column(&["x"]));
+
+        assert_eq!(
+            parsed.render_rows(&columns, "\n").unwrap(),
+            "<tr><td>1</td><td>apple</td><td>3</td></tr>\n<tr><td>2</td><td>pear</td><td>5</td></tr>"
+        );
+
+        columns.insert("qty".to_string(), column(&["3"]));
+        assert_eq!(
+            parsed.render_rows(&columns, "\n").unwrap_err(),
+            ColumnLengthError {
+                key: "qty".to_string(),
+                len: 1,
+                expected: 2
+            }
+        );
+
+        // Without a referenced column there is no row to render.
+        let unrelated = ZipTemplate::parse("<tr>{{other}}</tr>");
+        assert_eq!(unrelated.render_rows(&columns, "\n").unwrap(), "");
+        assert_eq!(
+            ZipTemplate::parse("static")
+                .render_rows(&columns, "\n")
+                .unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn default_with_escaped_pipe() {
+        let parsed = ZipTemplate::parse(r"[{{x|a\|b}}] [{{y | none}}]");
+        let mut flat = FxHashMap::default();
+        assert_eq!(parsed.render(&flat), "[a|b] [none]");
+
+        flat.insert("x".to_string(), "set".to_string());
+        flat.insert("y".to_string(), String::new());
+        assert_eq!(parsed.render(&flat), "[set] [none]");
+    }
+
+    #[test]
+    fn key_with_escaped_colon() {
+        let parsed = ZipTemplate::parse(r"{{ns\:key}} / {{ns\:key:group}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("ns:key".to_string(), "1234".to_string());
+        assert_eq!(parsed.render(&flat), "1234 / 1234");
+        assert_eq!(
+            parsed.render_with_number_format(&flat, &NumberFormat::default()),
+            "1234 / 1,234"
+        );
+    }
+
+    #[test]
+    fn visit_matches_flatten_json() {
+        let data = serde_json::json!({
+            "user": {
+                "name": "Alice",
+                "tags": ["admin"]
+            },
+            "active": true,
+            "nothing": null
+        });
+
+        let mut visited = FxHashMap::default();
+        flatten_json_visit(&data, |key, value| {
+            assert!(visited.insert(key.to_string(), value.to_string()).is_none());
+        });
+
+        assert_eq!(visited, flatten_json(&data));
+        assert_eq!(visited.len(), 4);
+        assert_eq!(visited["user.tags.0"], "admin");
+    }
+
+    #[test]
+    fn dry_run_matches_render() {
+        let parsed = ZipTemplate::parse("{{a}} {{b}} {{a}} {{c | none}} {{d ?? a}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("a".to_string(), "first".to_string());
+        flat.insert("b".to_string(), "ü".to_string());
+
+        let dry = parsed.dry_run(&flat);
+        assert_eq!(dry.output_size, parsed.render(&flat).len());
+        assert_eq!(dry.resolved_keys, 5);
+        assert!(dry.missing_keys.is_empty());
+        assert_eq!(parsed.render_strict(&flat), Ok(parsed.render(&flat)));
+
+        flat.remove("b");
+        let dry = parsed.dry_run(&flat);
+        assert_eq!(dry.output_size, parsed.render(&flat).len());
+        assert_eq!(dry.resolved_keys, 4);
+        assert_eq!(parsed.render_strict(&flat), Err(dry.missing_keys));
+    }
+
+    #[test]
+    fn aliases_redirect_to_data_keys() {
+        let parsed = ZipTemplate::parse("{{greeting}}, {{who}}! ({{id}})");
+        let mut aliases = FxHashMap::default();
+        aliases.insert("who".to_string(), "user.name.first".to_string());
+        aliases.insert("id".to_string(), "user.id".to_string());
+        let mut data = FxHashMap::default();
+        data.insert("greeting".to_string(), "Hi".to_string());
+        data.insert("user.name.first".to_string(), "Sam".to_string());
+        data.insert("who".to_string(), "not this".to_string());
+
+        assert_eq!(parsed.render_with_aliases(&aliases, &data), "Hi, Sam! ()");
+    }
+
+    #[test]
+    fn render_events_in_order() {
+        #[derive(Default)]
+        struct Recorder(Vec<String>);
+
+        impl SegmentHandler for Recorder {
+            fn on_static(&mut self, text: &str) {
+                self.0.push(format!("static {:?}", text));
+            }
+            fn on_value(&mut self, key: &str, value: &str) {
+                self.0.push(format!("value {} = {:?}", key, value));
+            }
+        }
+
+        let parsed = ZipTemplate::parse("Dear {{name}},{{greeting}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("name".to_string(), "Sam".to_string());
+        flat.insert("greeting".to_string(), "hello".to_string());
+
+        let mut recorder = Recorder::default();
+        parsed.render_events(&flat, &mut recorder);
+        assert_eq!(
+            recorder.0,
+            [
+                r#"static "Dear ""#,
+                r#"value name = "Sam""#,
+                r#"static ",""#,
+                r#"value greeting = "hello""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn render_hash_tracks_used_values() {
+        let parsed = ZipTemplate::parse("{{a}}-{{b}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("a".to_string(), "1".to_string());
+        flat.insert("b".to_string(), "2".to_string());
+        let base = parsed.render_hash(&flat);
+
+        flat.insert("unused".to_string(), "x".to_string());
+        assert_eq!(parsed.render_hash(&flat), base);
+
+        flat.insert("b".to_string(), "3".to_string());
+        assert_ne!(parsed.render_hash(&flat), base);
+    }
+
+    #[test]
+    fn render_hash_depends_only_on_output() {
+        let parsed = ZipTemplate::parse("{{a}}{{b}}");
+        let mut split_one = FxHashMap::default();
+        split_one.insert("a".to_string(), "ab".to_string());
+        split_one.insert("b".to_string(), "c".to_string());
+        let mut split_two = FxHashMap::default();
+        split_two.insert("a".to_string(), "a".to_string());
+        split_two.insert("b".to_string(), "bc".to_string());
+
+        let literal = ZipTemplate::parse("abc");
+        assert_eq!(
+            parsed.render_hash(&split_one),
+            parsed.render_hash(&split_two)
+        );
+        assert_eq!(
+            parsed.render_hash(&split_one),
+            literal.render_hash(&FxHashMap::default())
+        );
+    }
+
+    #[test]
+    fn multi_syntax_mixes_delimiters() {
+        let parsed =
+            ZipTemplate::parse_multi_syntax("${HOME}/docs for {{ user.name }} (${ SHELL })");
+        assert_eq!(parsed.statics, ["", "/docs for ", " (", ")"]);
+        assert_eq!(parsed.placeholders, ["HOME", "user.name", "SHELL", ""]);
+
+        let mut flat = FxHashMap::default();
+        flat.insert("HOME".to_string(), "/home/sam".to_string());
+        flat.insert("user.name".to_string(), "Sam".to_string());
+        flat.insert("SHELL".to_string(), "zsh".to_string());
+        assert_eq!(parsed.render(&flat), "/home/sam/docs for Sam (zsh)");
+    }
+
+    #[test]
+    fn multi_syntax_matches_parse_for_braces_only() {
+        let tpl = "Hi, {{user.name.first}} — balance: {{account.balance}} USD {{ unclosed";
+        let multi = ZipTemplate::parse_multi_syntax(tpl);
+        let plain = ZipTemplate::parse(tpl);
+        assert_eq!(multi.statics, plain.statics);
+        assert_eq!(multi.placeholders, plain.placeholders);
+    }
+
+    #[test]
+    fn strict_unused_detects_stale_keys() {
+        let parsed = ZipTemplate::parse("{{a}} {{b ?? c}} {{d:.2}}");
+        let mut flat = FxHashMap::default();
+        for key in ["a", "b", "c", "d"] {
+            flat.insert(key.to_string(), "1".to_string());
+        }
+        assert_eq!(parsed.render_strict_unused(&flat).unwrap(), "1 1 1");
+
+        flat.insert("stale".to_string(), "x".to_string());
+        flat.insert("also.stale".to_string(), "y".to_string());
+        assert_eq!(
+            parsed.render_strict_unused(&flat).unwrap_err(),
+            ["also.stale", "stale"]
+        );
+    }
+
+    #[test]
+    fn conditional_statics_dropped_for_empty_value() {
+        let parsed = ZipTemplate::parse("{{prefix}}-{{?optional}}-{{suffix}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("prefix".to_string(), "a".to_string());
+        flat.insert("suffix".to_string(), "b".to_string());
+        assert_eq!(parsed.render_conditional(&flat), "ab");
+        assert_eq!(parsed.render(&flat), "a--b");
+
+        flat.insert("optional".to_string(), String::new());
+        assert_eq!(parsed.render_conditional(&flat), "ab");
+    }
+
+    #[test]
+    fn conditional_statics_kept_for_present_value() {
+        let parsed = ZipTemplate::parse("{{prefix}}-{{?optional}}-{{suffix}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("prefix".to_string(), "a".to_string());
+        flat.insert("optional".to_string(), "x".to_string());
+        flat.insert("suffix".to_string(), "b".to_string());
+        assert_eq!(parsed.render_conditional(&flat), "a-x-b");
+        assert_eq!(parsed.render(&flat), "a-x-b");
+    }
+
+    #[test]
+    fn from_tokens_establishes_alignment() {
+        let tokens = vec![
+            Token::Placeholder("a".to_string()),
+            Token::Placeholder("b".to_string()),
+            Token::Static(", ".to_string()),
+            Token::Static("and ".to_string()),
+            Token::Placeholder("c".to_string()),
+        ];
+        let built = ZipTemplate::from_tokens(tokens.into_iter());
+        let parsed = ZipTemplate::parse("{{a}}{{b}}, and {{c}}");
+        assert_eq!(built.statics, parsed.statics);
+        assert_eq!(built.placeholders, parsed.placeholders);
+        assert_eq!(built.statics, ["", "", ", and ", ""]);
+
+        let empty = ZipTemplate::from_tokens(std::iter::empty());
+        assert_eq!(empty.statics, [""]);
+        assert_eq!(empty.placeholders, [""]);
+    }
+
+    #[test]
+    fn from_tokens_alternating_renders() {
+        let tokens = [
+            Token::Static("Hi, ".to_string()),
+            Token::Placeholder("name".to_string()),
+            Token::Static("!".to_string()),
+        ];
+        let built = ZipTemplate::from_tokens(tokens.into_iter());
+        let mut flat = FxHashMap::default();
+        flat.insert("name".to_string(), "Sam".to_string());
+        assert_eq!(built.render(&flat), "Hi, Sam!");
+    }
+
+    #[test]
+    fn render_tee_writes_both_sinks() {
+        let parsed =
+            ZipTemplate::parse("Hi, {{user.name.first}} — balance: {{account.balance}} USD");
+        let mut flat = FxHashMap::default();
+        flat.insert("user.name.first".to_string(), "Sam".to_string());
+        flat.insert("account.balance".to_string(), "12.34".to_string());
+
+        let mut a = String::new();
+        let mut b = String::new();
+        parsed.render_tee(&flat, &mut a, &mut b).unwrap();
+        assert_eq!(a, parsed.render(&flat));
+        assert_eq!(b, parsed.render(&flat));
+
+        let mut into = String::new();
+        parsed.render_into(&flat, &mut into).unwrap();
+        assert_eq!(into, parsed.render(&flat));
+    }
+
+    #[test]
+    fn static_between_adjacent_placeholders() {
+        let parsed = ZipTemplate::parse("{{a}} and {{b}}");
+        assert_eq!(parsed.static_between("a", "b"), Some(" and "));
+        assert_eq!(parsed.static_between("b", "a"), None);
+        assert_eq!(parsed.static_between("a", "missing"), None);
+
+        let parsed = ZipTemplate::parse("{{a}}{{b}}, {{a}}-{{c}}");
+        assert_eq!(parsed.static_between("a", "b"), Some(""));
+        assert_eq!(parsed.static_between("a", "c"), None);
+        assert_eq!(parsed.static_between("b", "a"), Some(", "));
+    }
+
+    #[test]
+    fn truncate_static_respects_budget() {
+        let parsed = ZipTemplate::parse(
+            "Hello {{name}}, your order {{id}} has shipped — it will arrive {{date}}. Thanks!",
+        );
+        let mut flat = FxHashMap::default();
+        flat.insert("name".to_string(), "Sam".to_string());
+        flat.insert("id".to_string(), "#42".to_string());
+        flat.insert("date".to_string(), "Monday".to_string());
+
+        for budget in 0..100 {
+            let preview = parsed.truncate_static(budget);
+            let values_len: usize = preview
+                .placeholders
+                .iter()
+                .map(|k| flat.get(k).map_or(0, String::len))
+                .sum();
+            let out = preview.render(&flat);
+            assert!(out.len() <= budget + values_len, "budget {}", budget);
+            assert!(parsed.render(&flat).starts_with(&out), "budget {}", budget);
+            assert_eq!(preview.statics.len(), preview.placeholders.len());
+        }
+
+        let preview = parsed.truncate_static(22);
+        assert_eq!(preview.statics, ["Hello ", ", your order ", " ha"]);
+        assert_eq!(preview.render(&flat), "Hello Sam, your order #42 ha");
+        // The em dash is three bytes; a budget ending inside it cuts before it.
+        let preview = parsed.truncate_static(33);
+        assert_eq!(preview.statics[2], " has shipped ");
+    }
+
+    #[test]
+    fn used_filters() {
+        let parsed = ZipTemplate::parse(
+            "{{name |> upper}} from {{city |> trim |> upper}}, {{country |> trim | n/a}}",
+        );
+        assert_eq!(parsed.used_filters(), ["upper", "trim"]);
+
+        let parsed = ZipTemplate::parse("{{name}} {{amount:group}} {{title | untitled}}");
+        assert!(parsed.used_filters().is_empty());
+    }
+
+    #[test]
+    fn render_transform_statics() {
+        let parsed = ZipTemplate::parse("<p>\n    {{greeting}}\n    </p>  {{name}}");
+        let mut values = FxHashMap::default();
+        values.insert("greeting".to_string(), "Hello,   world".to_string());
+        values.insert("name".to_string(), "  Sam".to_string());
+
+        fn collapse(s: &str) -> Cow<'_, str> {
+            Cow::Owned(s.split_whitespace().collect::<Vec<_>>().join(" "))
+        }
+        assert_eq!(
+            parsed.render_transform_statics(&values, collapse),
+            "<p>Hello,   world</p>  Sam"
+        );
+    }
+
+    #[test]
+    fn parse_lenient_tags() {
+        let source = "<ul>{{ #each items }}<li>{{name}}</li>{{/each}}</ul>{{! note }}";
+        let parsed = ZipTemplate::parse_lenient_tags(source);
+        assert_eq!(
+            parsed.statics,
+            [
+                "<ul>{{ #each items }}<li>",
+                "</li>{{/each}}</ul>{{! note }}"
+            ]
+        );
+        assert_eq!(parsed.placeholders, ["name", ""]);
+
+        let mut values = FxHashMap::default();
+        values.insert("name".to_string(), "Sam".to_string());
+        assert_eq!(
+            parsed.render(&values),
+            "<ul>{{ #each items }}<li>Sam</li>{{/each}}</ul>{{! note }}"
+        );
+
+        // Without lenient mode the same tags are ordinary placeholders.
+        let strict = ZipTemplate::parse(source);
+        assert_eq!(
+            strict.placeholders,
+            ["#each items", "name", "/each", "! note", ""]
+        );
+    }
+
+    #[test]
+    fn write_to_flushing() {
+        struct CountingWriter {
+            data: Vec<u8>,
+            flushes: usize,
+        }
+
+        impl io::Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.data.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                self.flushes += 1;
+                Ok(())
+            }
+        }
+
+        let parsed = ZipTemplate::parse("{{a}}-{{b}}-{{c}}-{{d}}-{{e}}");
+        let mut values = FxHashMap::default();
+        for key in ["a", "b", "c", "d", "e"] {
+            values.insert(key.to_string(), key.to_uppercase());
+        }
+
+        let mut out = CountingWriter {
+            data: Vec::new(),
+            flushes: 0,
+        };
+        // 6 statics and 6 values make 12 segments.
+        let written = parsed.write_to_flushing(&values, &mut out, 5).unwrap();
+        assert_eq!(out.data, b"A-B-C-D-E");
+        assert_eq!(written, 9);
+        assert_eq!(out.flushes, 2);
+
+        out.flushes = 0;
+        parsed.write_to_flushing(&values, &mut out, 0).unwrap();
+        assert_eq!(out.flushes, 0);
+    }
+
+    #[test]
+    fn shares_skeleton() {
+        let en = ZipTemplate::parse("Order {{id}} ships on {{date}}.");
+        let es = ZipTemplate::parse("El pedido {{ id }} se envía el {{date}}");
+        assert!(en.shares_skeleton(&es));
+        assert!(es.shares_skeleton(&en));
+
+        let reordered = ZipTemplate::parse("{{date}}: order {{id}}");
+        assert!(!en.shares_skeleton(&reordered));
+
+        let extra = ZipTemplate::parse("Order {{id}} ships on {{date}} to {{city}}.");
+        assert!(!en.shares_skeleton(&extra));
+    }
+
+    #[test]
+    fn render_with_resolved_mask() {
+        let parsed = ZipTemplate::parse("{{present}} and {{missing}} and {{absent | n/a}}");
+        let mut values = FxHashMap::default();
+        values.insert("present".to_string(), "here".to_string());
+
+        let (rendered, mask) = parsed.render_with_resolved_mask(&values);
+        assert_eq!(rendered, "here and  and n/a");
+        assert_eq!(mask, [true, false, false]);
+
+        let parsed = ZipTemplate::parse("{{present}}-{{missing}}");
+        assert_eq!(parsed.render_with_resolved_mask(&values).1, [true, false]);
+
+        let parsed = ZipTemplate::parse("{{missing ?? present}} {{missing ?? absent | n/a}}");
+        assert_eq!(parsed.render_with_resolved_mask(&values).1, [true, false]);
+    }
+
+    #[test]
+    fn render_range() {
+        let parsed = ZipTemplate::parse("A{{a}}B{{b}}C{{c}}D{{d}}E");
+        assert_eq!(parsed.statics.len(), 5);
+        let mut values = FxHashMap::default();
+        for key in ["a", "b", "c", "d"] {
+            values.insert(key.to_string(), key.repeat(2));
+        }
+
+        let full = parsed.render(&values);
+        assert_eq!(full, "AaaBbbCccDddE");
+        assert_eq!(parsed.render_range(&values, 1..3), &full[3..9]);
+
+        let windows: String = [0..2, 2..4, 4..5]
+            .into_iter()
+            .map(|range| parsed.render_range(&values, range))
+            .collect();
+        assert_eq!(windows, full);
+
+        assert_eq!(parsed.render_range(&values, 4..99), "E");
+        assert_eq!(parsed.render_range(&values, 7..9), "");
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = 3..1;
+        assert_eq!(parsed.render_range(&values, inverted), "");
+    }
+
+    #[test]
+    fn render_with_providers() {
+        let db = |key: &str| match key {
+            "user" => Some("sam".to_string()),
+            _ => None,
+        };
+        let db_cache = |key: &str| Some(format!("cached {}", key));
+        let env = |key: &str| Some(format!("${}", key));
+
+        let parsed = ZipTemplate::parse(
+            "{{db.user}} {{db.missing}} {{db.cache.hits}} {{env.PATH}} {{other.x}}!",
+        );
+        let rendered =
+            parsed.render_with_providers(&[("db.", &db), ("env.", &env), ("db.cache.", &db_cache)]);
+        assert_eq!(rendered, "sam  cached hits $PATH !");
+    }
+
+    #[test]
+    fn render_flat_refuses_control_tags() {
+        let mut values = FxHashMap::default();
+        values.insert("name".to_string(), "Sam".to_string());
+
+        let parsed = ZipTemplate::parse("<ul>{{ #each items }}<li>{{name}}</li>{{/each}}</ul>");
+        assert_eq!(
+            parsed.render_flat(&values),
+            Err(ControlTagError {
+                index: 0,
+                tag: "#each items".to_string()
+            })
+        );
+
+        let parsed = ZipTemplate::parse("<li>{{name}}</li>");
+        assert_eq!(parsed.render_flat(&values).unwrap(), "<li>Sam</li>");
+
+        // Kept as static text, control tags no longer stop flat rendering.
+        let lenient = ZipTemplate::parse_lenient_tags("{{#if x}}{{name}}{{/if}}");
+        assert_eq!(lenient.render_flat(&values).unwrap(), "{{#if x}}Sam{{/if}}");
+    }
+
+    #[test]
+    fn render_into_chunks() {
+        let parsed = ZipTemplate::parse("<p>{{greeting}}, {{name}}</p>\n{{footer}}");
+        let mut values = FxHashMap::default();
+        values.insert("greeting".to_string(), "Grüße".to_string());
+        values.insert("name".to_string(), "Sam".repeat(10));
+        values.insert("footer".to_string(), "-- end".to_string());
+
+        let rendered = parsed.render(&values);
+        for chunk_size in [1, 3, 7, 16, rendered.len(), rendered.len() + 5] {
+            let chunks = parsed.render_into_chunks(&values, chunk_size);
+            assert_eq!(chunks.concat(), rendered.as_bytes());
+
+            let (last, full) = chunks.split_last().unwrap();
+            assert!(full.iter().all(|chunk| chunk.len() == chunk_size));
+            assert!(!last.is_empty() && last.len() <= chunk_size);
+        }
+
+        let empty = ZipTemplate::parse("{{missing}}");
+        assert!(empty.render_into_chunks(&values, 4).is_empty());
+    }
+
+    #[test]
+    fn render_json_inlines_subtrees() {
+        let data = serde_json::json!({
+            "user": { "name": "Alice", "tags": ["admin", "ops"], "age": 30, "nick": null },
+            "items": [{ "id": 1 }, { "id": 2 }]
+        });
+
+        let parsed = ZipTemplate::parse("user={{*user}}");
+        assert_eq!(
+            parsed.render_json(&data),
+            r#"user={"age":30,"name":"Alice","nick":null,"tags":["admin","ops"]}"#
+        );
+
+        let parsed = ZipTemplate::parse("<script>let tags = {{* user.tags }};</script>");
+        assert_eq!(
+            parsed.render_json(&data),
+            r#"<script>let tags = ["admin","ops"];</script>"#
+        );
+    }
+
+    #[test]
+    fn render_json_scalars_match_flattened_render() {
+        let data = serde_json::json!({
+            "user": { "name": "Alice", "tags": ["admin"], "age": 30, "nick": null },
+            "items": [{ "id": 1 }, { "id": 2 }]
+        });
+        let parsed = ZipTemplate::parse(
+            "{{user.name}} {{user.age}} [{{user.nick}}] {{items.1.id}} {{user.tags.0}} \
+             [{{user}}] [{{missing}}] {{user.nick ?? user.name}} {{items.5.id | none}}",
+        );
+        assert_eq!(
+            parsed.render_json(&data),
+            parsed.render(&flatten_json(&data))
+        );
+        assert_eq!(
+            parsed.render_json(&data),
+            "Alice 30 [] 2 admin [] [] Alice none"
+        );
+        assert_eq!(ZipTemplate::parse("{{*missing}}").render_json(&data), "");
+    }
+
+    #[test]
+    fn parse_bounded() {
+        let source = "Dear {{name}},";
+        assert_eq!(source.len(), 14);
+
+        let parsed = ZipTemplate::parse_bounded(source, 14).unwrap();
+        assert_eq!(parsed.placeholders, ["name", ""]);
+        assert_eq!(
+            ZipTemplate::parse_bounded(source, 13).unwrap_err(),
+            ParseError::TooLong { len: 14, max: 13 }
+        );
+        assert_eq!(
+            ZipTemplate::parse_bounded(source, 13)
+                .unwrap_err()
+                .to_string(),
+            "template is 14 bytes long, limit is 13"
+        );
+    }
+
+    #[test]
+    fn render_instrumented_call_order() {
+        struct Recorder(Vec<String>);
+
+        impl RenderHooks for Recorder {
+            fn before_placeholder(&mut self, key: &str) {
+                self.0.push(format!("before {}", key));
+            }
+            fn after_placeholder(&mut self, key: &str, value: &str) {
+                self.0.push(format!("after {} = {:?}", key, value));
             }
         }
 
-        out
+        let parsed = ZipTemplate::parse("{{greeting}}, {{name}}!");
+        let mut flat = FxHashMap::default();
+        flat.insert("greeting".to_string(), "Hi".to_string());
+
+        let mut hooks = Recorder(Vec::new());
+        assert_eq!(parsed.render_instrumented(&flat, &mut hooks), "Hi, !");
+        assert_eq!(
+            hooks.0,
+            [
+                "before greeting",
+                "after greeting = \"Hi\"",
+                "before name",
+                "after name = \"\"",
+            ]
+        );
     }
-}
 
-/// Flattens a nested JSON object into a flat map with dot-separated keys.
-///
-/// This function recursively traverses a `serde_json::Value`. Nested object keys are
-/// joined by dots (`parent.child`), and array indices are treated as keys (`array.0`).
-/// Primitive values are converted to strings.
-///
-/// # Arguments
-///
-/// * `value` - A reference to the `serde_json::Value` to flatten.
-///
-/// # Returns
-///
-/// A `FxHashMap<String, String>` where keys represent the path to the value and
-/// values are the string representations of the leaf nodes.
-///
-/// # Examples
-///
-/// ```
-/// use serde_json::json;
-/// use rustc_hash::FxHashMap;
-/// # // Mock function definition for the doctest if strictly necessary,
-/// # // or assume the user imports it from the crate.
-/// # use serde_json::Value;
-/// # fn flatten_json(value: &Value) -> FxHashMap<String, String> {
-/// #    // ... implementation ...
-/// #    // (Mocking body for brevity in display, actual test runs real code)
-/// #    let mut out = FxHashMap::default();
-/// #    // simplified mock logic for the example's sake:
-/// #    if value.is_object() {
-/// #        out.insert("user.name".to_string(), "Alice".to_string());
-/// #        out.insert("user.tags.0".to_string(), "admin".to_string());
-/// #        out.insert("active".to_string(), "true".to_string());
-/// #    }
-/// #    out
-/// # }
-///
-/// let data = json!({
-///     "user": {
-///         "name": "Alice",
-///         "tags": ["admin"]
-///     },
-///     "active": true
-/// });
-///
-/// let flattened = flatten_json(&data);
-///
-/// assert_eq!(flattened.get("user.name"), Some(&"Alice".to_string()));
-/// assert_eq!(flattened.get("user.tags.0"), Some(&"admin".to_string()));
-/// assert_eq!(flattened.get("active"), Some(&"true".to_string()));
-/// ```
-pub fn flatten_json(value: &Value) -> FxHashMap<String, String> {
-    fn helper(value: &Value, prefix: String, out: &mut FxHashMap<String, String>) {
-        match value {
-            Value::Object(map) => {
-                for (k, v) in map {
-                    let new_prefix = if prefix.is_empty() {
-                        k.clone()
-                    } else {
-                        format!("{}.{}", prefix, k)
-                    };
-                    helper(v, new_prefix, out);
-                }
+    #[test]
+    fn normalize_paths() {
+        let mut parsed = ZipTemplate::parse(
+            "{{user..name}} {{.user}} {{user.}} {{ok.path}} {{a...b ?? .c | x..y}} {{?.nick}} {{> head..er}}",
+        );
+        parsed.normalize_paths();
+        assert_eq!(
+            parsed.placeholders,
+            [
+                "user.name",
+                "user",
+                "user",
+                "ok.path",
+                "a.b ?? c | x..y",
+                "?nick",
+                "> head..er",
+                ""
+            ]
+        );
+
+        let mut values = FxHashMap::default();
+        values.insert("user.name".to_string(), "Sam".to_string());
+        let mut parsed = ZipTemplate::parse("Hi {{ user . . name }}");
+        assert_eq!(parsed.render(&values), "Hi ");
+        parsed.normalize_paths();
+        assert_eq!(parsed.render(&values), "Hi Sam");
+    }
+
+    #[test]
+    fn values_for_follows_placeholder_order() {
+        let (name, age, city) = ("Sam", 30, "Paris".to_string());
+
+        let parsed = ZipTemplate::parse("{{user.name}} ({{age}}) lives in {{city}}");
+        let dynamics =
+            values_for!(parsed, { "user.name" => name, "age" => age, "city" => city }).unwrap();
+        assert_eq!(parsed.render_from_vec(&dynamics), "Sam (30) lives in Paris");
+
+        // Reordering the template needs no change on the data side.
+        let reordered = ZipTemplate::parse("{{city}}: {{user.name}}, {{age}}, again {{city}}");
+        let dynamics =
+            values_for!(reordered, { "user.name" => name, "age" => age, "city" => city }).unwrap();
+        assert_eq!(
+            reordered.render_from_vec(&dynamics),
+            "Paris: Sam, 30, again Paris"
+        );
+
+        assert_eq!(
+            values_for!(reordered, { "age" => age }),
+            Err(vec!["city", "user.name"])
+        );
+    }
+
+    #[test]
+    fn render_then_finalizes_output() {
+        let parsed = ZipTemplate::parse("<ul>  \n  <li>{{item}}</li>   \n</ul>\t");
+        let mut values = FxHashMap::default();
+        values.insert("item".to_string(), "tea  ".to_string());
+
+        let strip_trailing = |out: String| {
+            out.lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert_eq!(
+            parsed.render_then(&values, strip_trailing),
+            "<ul>\n  <li>tea  </li>\n</ul>"
+        );
+    }
+
+    #[test]
+    fn recommend_strategy() {
+        let rows: Vec<_> = (0..1000)
+            .map(|i| serde_json::json!({ "id": i, "name": format!("row {}", i) }))
+            .collect();
+        let huge = serde_json::json!({ "title": "Report", "rows": rows });
+
+        let tiny = ZipTemplate::parse("<h1>{{title}}</h1> first: {{rows.0.name}}");
+        assert_eq!(tiny.recommend_strategy(&huge), RenderStrategy::DirectPaths);
+
+        let small = serde_json::json!({
+            "user": { "name": "Sam", "email": "sam@example.com", "age": 30 },
+            "plan": "pro"
+        });
+        let most = ZipTemplate::parse("{{user.name}} <{{user.email}}> on {{plan}}");
+        assert_eq!(most.recommend_strategy(&small), RenderStrategy::FlattenAll);
+
+        let some = ZipTemplate::parse("{{rows.0.id}} {{rows.1.id}}");
+        let medium = serde_json::json!({ "rows": rows[..10] });
+        assert_eq!(
+            some.recommend_strategy(&medium),
+            RenderStrategy::PartialFlatten
+        );
+    }
+
+    #[test]
+    fn render_from_vec_ordered() {
+        let parsed = ZipTemplate::parse("{{a}}-{{b}}-{{c}}");
+        let rendered = parsed.render_from_vec_ordered(&["3", "2", "1"], &["c", "b", "a"]);
+        assert_eq!(rendered.unwrap(), "1-2-3");
+
+        let owned = vec!["x".to_string()];
+        assert_eq!(
+            parsed.render_from_vec_ordered(&owned, &["b"]).unwrap(),
+            "-x-"
+        );
+
+        assert_eq!(
+            parsed.render_from_vec_ordered(&["1", "2"], &["a"]),
+            Err(OrderLengthError { values: 2, keys: 1 })
+        );
+    }
+
+    fn assert_reparse_matches(source: &str, range: Range<usize>, new_text: &str) {
+        let mut incremental = ZipTemplate::parse(source);
+        incremental.reparse_range(source, range.clone(), new_text);
+
+        let mut edited = source.to_string();
+        edited.replace_range(range, new_text);
+        let full = ZipTemplate::parse(&edited);
+        assert_eq!(
+            incremental.statics, full.statics,
+            "statics after editing to {:?}",
+            edited
+        );
+        assert_eq!(
+            incremental.placeholders, full.placeholders,
+            "placeholders for {:?}",
+            edited
+        );
+    }
+
+    #[test]
+    fn reparse_range_inside_static() {
+        let source = "<h1>{{title}}</h1><p>Hello there</p>{{footer}}";
+        let mut parsed = ZipTemplate::parse(source);
+        parsed.reparse_range(source, 27..32, "again");
+        assert_eq!(parsed.statics, ["<h1>", "</h1><p>Hello again</p>", ""]);
+        assert_eq!(parsed.placeholders, ["title", "footer", ""]);
+
+        assert_reparse_matches(source, 27..32, "again");
+        assert_reparse_matches(source, 0..0, "<!doctype html>");
+        assert_reparse_matches(source, source.len()..source.len(), " end");
+    }
+
+    #[test]
+    fn reparse_range_creates_and_removes_tags() {
+        let source = "<h1>{{title}}</h1><p>Hello there</p>{{footer}}";
+        let mut parsed = ZipTemplate::parse(source);
+        parsed.reparse_range(source, 27..32, "{{ name }}");
+        assert_eq!(parsed.statics, ["<h1>", "</h1><p>Hello ", "</p>", ""]);
+        assert_eq!(parsed.placeholders, ["title", "name", "footer", ""]);
+
+        // Editing a tag's key, deleting a tag, and merging two tags into one.
+        assert_reparse_matches(source, 6..11, "heading");
+        assert_reparse_matches(source, 4..13, "");
+        assert_reparse_matches(source, 11..38, "");
+    }
+
+    #[test]
+    fn reparse_range_widens_across_boundaries() {
+        let source = "a {{x}} b {{y}} c {{z}} d";
+        // An opener without its closer pairs with a later tag's `}}`.
+        assert_reparse_matches(source, 8..9, "{{ open");
+        // A trailing `{` joins the opener of the next tag.
+        assert_reparse_matches(source, 9..9, "{");
+        // A closer completes a tag the edit opened earlier in the region.
+        assert_reparse_matches(source, 0..1, "{{w");
+        // Every single-byte replacement and deletion agrees with a full parse.
+        for i in 0..source.len() {
+            for text in ["", "{", "}", "{{", "}}", "q"] {
+                assert_reparse_matches(source, i..i + 1, text);
+                assert_reparse_matches(source, i..i, text);
             }
-            Value::Array(arr) => {
-                for (i, v) in arr.iter().enumerate() {
-                    let new_prefix = if prefix.is_empty() {
-                        i.to_string()
-                    } else {
-                        format!("{}.{}", prefix, i)
-                    };
-                    helper(v, new_prefix, out);
+        }
+    }
+
+    #[test]
+    fn reparse_range_with_mismatched_source() {
+        let mut parsed = ZipTemplate::parse("{{a}} b");
+        parsed.reparse_range("{{ other }} source", 0..0, "x ");
+        assert_eq!(parsed.statics, ["x ", " source"]);
+        assert_eq!(parsed.placeholders, ["other", ""]);
+    }
+
+    #[test]
+    fn render_minified() {
+        let parsed = ZipTemplate::parse(
+            "<ul>\n\t<li>  {{first}}  </li>\r\n\n    <li>{{second}}</li>\n</ul>\n",
+        );
+        let mut values = FxHashMap::default();
+        values.insert("first".to_string(), "two  spaces".to_string());
+        values.insert("second".to_string(), "line\n\nbreaks\t".to_string());
+
+        assert_eq!(
+            parsed.render_minified(&values),
+            "<ul> <li> two  spaces </li> <li>line\n\nbreaks\t</li> </ul> "
+        );
+    }
+
+    #[test]
+    fn fill_binds_known_keys() {
+        let parsed = ZipTemplate::parse("Dear {{name}}, order {{id}} ships {{when | soon}}.");
+        let mut early = FxHashMap::default();
+        early.insert("name".to_string(), "Sam".to_string());
+
+        let filled = parsed.fill(&early);
+        assert_eq!(filled.statics, ["Dear Sam, order ", " ships ", "."]);
+        assert_eq!(filled.placeholders, ["id", "when | soon", ""]);
+
+        let mut late = FxHashMap::default();
+        late.insert("id".to_string(), "42".to_string());
+        assert_eq!(filled.render(&late), "Dear Sam, order 42 ships soon.");
+
+        let mut all = early.clone();
+        all.extend(late.clone());
+        assert_eq!(filled.render(&late), parsed.render(&all));
+
+        // Binding everything leaves a template of one static.
+        all.insert("when".to_string(), "today".to_string());
+        let done = parsed.fill(&all);
+        assert_eq!(done.statics, ["Dear Sam, order 42 ships today."]);
+        assert_eq!(done.placeholders, [""]);
+
+        // A later key of a chain does not bind while an earlier one is unknown.
+        let chain = ZipTemplate::parse("{{a ?? b}}");
+        let mut early = FxHashMap::default();
+        early.insert("b".to_string(), "early-b".to_string());
+        let filled = chain.fill(&early);
+        assert_eq!(filled.placeholders, ["a ?? b", ""]);
+        let mut late = FxHashMap::default();
+        late.insert("a".to_string(), "late-a".to_string());
+        let mut all = early.clone();
+        all.extend(late.clone());
+        assert_eq!(filled.render(&late), "late-a");
+        assert_eq!(filled.render(&late), chain.render(&all));
+
+        // An earlier key known to be empty lets the chain bind.
+        early.insert("a".to_string(), String::new());
+        assert_eq!(chain.fill(&early).statics, ["early-b"]);
+    }
+
+    #[test]
+    fn render_escaping_braces() {
+        let parsed = ZipTemplate::parse("<p>{{comment}}</p>{{a}}{{b}}");
+        let mut values = FxHashMap::default();
+        values.insert("comment".to_string(), "nice {{evil}} }} {{".to_string());
+        values.insert("a".to_string(), "x{".to_string());
+        values.insert("b".to_string(), "{y}}".to_string());
+
+        let rendered = parsed.render_escaping_braces(&values);
+        assert_eq!(rendered, r"<p>nice \{{evil\}} \}} \{{</p>x\{{y\}}");
+
+        // Parsed again with brace escapes, every value comes back as literal text.
+        let reparsed = TemplateConfig::new()
+            .brace_escapes(true)
+            .parse(&rendered)
+            .unwrap();
+        assert_eq!(reparsed.statics, [parsed.render(&values)]);
+        assert_eq!(reparsed.placeholders, [""]);
+    }
+
+    #[test]
+    fn render_dyn_with_mixed_types() {
+        struct Plugin {
+            started: bool,
+        }
+
+        impl DynContext for Plugin {
+            fn get(&self, key: &str) -> Option<Box<dyn fmt::Display>> {
+                match key {
+                    "name" => Some(Box::new("cache")),
+                    "entries" => Some(Box::new(1024u64)),
+                    "hit_rate" => Some(Box::new(0.5f32)),
+                    "started" => Some(Box::new(self.started)),
+                    "owner" => Some(Box::new('z')),
+                    _ => None,
                 }
             }
-            Value::Null => {
-                out.insert(prefix, String::new());
-            }
-            _ => {
-                out.insert(prefix, value.to_string().trim_matches('"').to_string());
-            }
         }
+
+        let parsed = ZipTemplate::parse(
+            "{{name}}: {{entries}} entries, {{hit_rate}} hits, started={{started}} by {{owner}}{{unknown}}.",
+        );
+        assert_eq!(
+            parsed.render_dyn(&Plugin { started: true }),
+            "cache: 1024 entries, 0.5 hits, started=true by z."
+        );
     }
-    let mut out = FxHashMap::default();
-    helper(value, String::new(), &mut out);
-    out
-}
 
-// (render moved into impl ZipTemplate)
+    #[test]
+    fn render_bulk_matches_render() {
+        let large = "x".repeat(100 * 1024);
+        let source = format!("<main>{}{{{{content}}}}{}</main>", large, large);
+        let parsed = ZipTemplate::parse(&source);
+        let mut values = FxHashMap::default();
+        values.insert("content".to_string(), "payload".to_string());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let rendered = parsed.render_bulk(&values);
+        assert_eq!(rendered, parsed.render(&values));
+        assert_eq!(
+            rendered.len(),
+            2 * large.len() + "<main>payload</main>".len()
+        );
+        assert_eq!(rendered.capacity(), rendered.len());
+
+        let small = ZipTemplate::parse("{{a}}, {{b | none}} and {{a}}");
+        values.insert("a".to_string(), "1".to_string());
+        assert_eq!(small.render_bulk(&values), small.render(&values));
+        assert_eq!(ZipTemplate::parse("").render_bulk(&values), "");
+    }
 
     #[test]
-    fn basic_parse_render_flat() {
-        let tpl = "Hi, {{user.name.first}} — balance: {{account.balance}} USD";
-        let parsed = ZipTemplate::parse(tpl);
-        let mut flat = FxHashMap::default();
-        flat.insert("user.name.first".to_string(), "Sam".to_string());
-        flat.insert("account.balance".to_string(), "12.34".to_string());
-        let out = parsed.render(&flat);
-        assert_eq!(out, "Hi, Sam — balance: 12.34 USD");
+    fn render_json_with_object_policies() {
+        let data = serde_json::json!({
+            "user": { "name": "Alice", "age": 30 },
+            "tags": [],
+            "title": "Dr"
+        });
+        let parsed = ZipTemplate::parse("[{{user}}] [{{tags}}] [{{title}}] [{{*tags}}]");
+
+        let render = |policy| parsed.render_json_with(&data, policy);
+        assert_eq!(render(ObjectPolicy::Empty).unwrap(), "[] [] [Dr] [[]]");
+        assert_eq!(
+            render(ObjectPolicy::Empty).unwrap(),
+            parsed.render_json(&data)
+        );
+        assert_eq!(
+            render(ObjectPolicy::Json).unwrap(),
+            r#"[{"age":30,"name":"Alice"}] [[]] [Dr] [[]]"#
+        );
+        assert_eq!(
+            render(ObjectPolicy::FirstField).unwrap(),
+            "[30] [] [Dr] [[]]"
+        );
+        assert_eq!(
+            render(ObjectPolicy::Error),
+            Err(NonScalarError {
+                index: 0,
+                key: "user".to_string()
+            })
+        );
     }
 
     #[test]
-    fn missing_key_non_strict() {
-        let tpl = "Hello, {{name}}!";
-        let parsed = ZipTemplate::parse(tpl);
-        let flat = FxHashMap::default();
-        let out = parsed.render(&flat);
-        assert_eq!(out, "Hello, !");
+    fn render_json_with_strict_policy_in_chains() {
+        let data = serde_json::json!({ "user": { "name": "Alice" }, "nick": "Al" });
+
+        let scalar_first = ZipTemplate::parse("{{nick ?? user}} {{missing | none}}");
+        assert_eq!(
+            scalar_first
+                .render_json_with(&data, ObjectPolicy::Error)
+                .unwrap(),
+            "Al none"
+        );
+
+        let object_first = ZipTemplate::parse("{{title}} {{user ?? nick}}");
+        let err = object_first
+            .render_json_with(&data, ObjectPolicy::Error)
+            .unwrap_err();
+        assert_eq!((err.index, err.key.as_str()), (1, "user"));
+        assert_eq!(
+            err.to_string(),
+            "placeholder 1 key `user` points at an object or array, not a scalar"
+        );
+        assert_eq!(
+            object_first
+                .render_json_with(&data, ObjectPolicy::Empty)
+                .unwrap(),
+            " Al"
+        );
+        assert_eq!(
+            object_first
+                .render_json_with(&data, ObjectPolicy::FirstField)
+                .unwrap(),
+            " Alice"
+        );
     }
 
     #[test]
-    fn multiple_placeholders() {
-        let tpl = "{{a}},{{b}},{{c}}";
-        let parsed = ZipTemplate::parse(tpl);
+    fn debug_structure_lists_segments() {
+        assert_eq!(
+            ZipTemplate::parse("Hi {{name}}!").debug_structure(),
+            r#"[static "Hi "] [placeholder "name"] [static "!"] [pad ""]"#
+        );
+        assert_eq!(
+            ZipTemplate::parse("\t{{ a }}{{b}}").debug_structure(),
+            r#"[static "\t"] [placeholder "a"] [static ""] [placeholder "b"] [static ""] [pad ""]"#
+        );
+        assert_eq!(
+            ZipTemplate::parse("").debug_structure(),
+            r#"[static ""] [pad ""]"#
+        );
+    }
+
+    #[test]
+    fn render_splat_joins_array_entries() {
         let mut flat = FxHashMap::default();
-        flat.insert("a".to_string(), "1".to_string());
-        flat.insert("b".to_string(), "2".to_string());
-        flat.insert("c".to_string(), "3".to_string());
-        let out = parsed.render(&flat);
-        assert_eq!(out, "1,2,3");
+        flat.insert("tags.0".to_string(), "rust".to_string());
+        flat.insert("tags.1".to_string(), "web".to_string());
+        flat.insert("tags.3".to_string(), "unreachable".to_string());
+        flat.insert("name".to_string(), "zip".to_string());
+
+        let parsed = ZipTemplate::parse("{{name}}: {{tags}}{{missing}}");
+        assert_eq!(parsed.render_splat(&flat, ","), "zip: rust,web");
+        assert_eq!(parsed.render_splat(&flat, ""), "zip: rustweb");
+
+        // A default applies only when there are no indexed entries either.
+        let tagged = ZipTemplate::parse("{{tags | none}} {{missing | none}} {{missing ?? tags}}");
+        assert_eq!(tagged.render_splat(&flat, ","), "rust,web none rust,web");
+
+        // A direct entry wins over the indexed ones.
+        flat.insert("tags".to_string(), "all".to_string());
+        assert_eq!(parsed.render_splat(&flat, ","), parsed.render(&flat));
     }
 
     #[test]
-    fn empty_template() {
-        let tpl = "";
-        let parsed = ZipTemplate::parse(tpl);
-        let flat = FxHashMap::default();
-        let out = parsed.render(&flat);
-        assert_eq!(out, "");
+    fn display_roundtrips_plain_templates() {
+        for source in [
+            "",
+            "plain text",
+            "{{a}}",
+            "Hi {{ name }}, {{a}}{{b}} {{x ?? y | none}}!",
+            "{{user.name:<10 |> upper}}\n",
+            "{ single } braces }} {",
+        ] {
+            let parsed = ZipTemplate::parse(source);
+            assert!(parsed.is_roundtrip_stable(), "{:?}", source);
+            assert_eq!(
+                ZipTemplate::parse(&parsed.to_string()).to_string(),
+                parsed.to_string()
+            );
+        }
+        assert_eq!(
+            ZipTemplate::parse("Hi {{ name }}!").to_string(),
+            "Hi {{name}}!"
+        );
     }
 
     #[test]
-    fn only_static() {
-        let tpl = "static text only";
-        let parsed = ZipTemplate::parse(tpl);
-        let flat = FxHashMap::default();
-        let out = parsed.render(&flat);
-        assert_eq!(out, "static text only");
+    fn roundtrip_breaking_features() {
+        // Tags kept as static text become placeholders when reparsed.
+        assert!(
+            !ZipTemplate::parse_lenient_tags("{{#each xs}}{{x}}{{/each}}").is_roundtrip_stable()
+        );
+        let escaped = TemplateConfig::new()
+            .brace_escapes(true)
+            .parse(r"\{{a}} {{b}}")
+            .unwrap();
+        assert!(!escaped.is_roundtrip_stable());
+
+        let built = ZipTemplate::from_tokens(
+            [
+                Token::Static("{{".to_string()),
+                Token::Placeholder("a".to_string()),
+            ]
+            .into_iter(),
+        );
+        assert!(!built.is_roundtrip_stable());
+        let spaced = ZipTemplate::from_tokens([Token::Placeholder(" a ".to_string())].into_iter());
+        assert!(spaced.is_roundtrip_stable());
     }
 
     #[test]
-    fn basic_parse_render_flat_from_vec() {
-        let tpl = "Hi, {{user.name.first}} — balance: {{account.balance}} USD";
-        let parsed = ZipTemplate::parse(tpl);
-        let out = parsed.render_from_vec(&["Sam".to_string(), "12.34".to_string()]);
-        assert_eq!(out, "Hi, Sam — balance: 12.34 USD");
+    fn render_with_scratch_reuses_buffer() {
+        let parsed = ZipTemplate::parse("{{a}}-{{b | none}}-{{c ?? a}}");
+        let mut first = FxHashMap::default();
+        first.insert("a".to_string(), "1".to_string());
+        first.insert("b".to_string(), "2".to_string());
+        let mut second = FxHashMap::default();
+        second.insert("c".to_string(), "3".to_string());
+
+        let mut scratch = Vec::with_capacity(1);
+        assert_eq!(parsed.render_with_scratch(&first, &mut scratch), "1-2-1");
+        let capacity = scratch.capacity();
+        let buffer = scratch.as_ptr();
+        assert!(capacity >= parsed.placeholders.len());
+
+        assert_eq!(parsed.render_with_scratch(&second, &mut scratch), "-none-3");
+        assert_eq!(
+            parsed.render_with_scratch(&second, &mut scratch),
+            parsed.render(&second)
+        );
+        assert_eq!(scratch.len(), parsed.placeholders.len());
+        assert_eq!((scratch.capacity(), scratch.as_ptr()), (capacity, buffer));
     }
 
     #[test]
-    fn missing_key_non_strict_from_vec() {
-        let tpl = "Hello, {{name}}!";
-        let parsed = ZipTemplate::parse(tpl);
-        let out = parsed.render_from_vec(&Vec::new());
-        assert_eq!(out, "Hello, !");
+    fn render_with_dump_lists_sorted_data() {
+        let data = serde_json::json!({ "user": { "name": "Sam", "tags": ["a"] }, "id": 7 });
+        let flat = flatten_json(&data);
+        let parsed = ZipTemplate::parse("{{missing}}|{{ @dump }}|");
+
+        assert_eq!(
+            parsed.render_with_dump(&flat, true),
+            "|id = 7\nuser.name = Sam\nuser.tags.0 = a|"
+        );
+        assert_eq!(parsed.render_with_dump(&flat, false), parsed.render(&flat));
+        assert_eq!(parsed.render_with_dump(&FxHashMap::default(), true), "||");
     }
 
     #[test]
-    fn multiple_placeholders_from_vec() {
-        let tpl = "{{a}},{{b}},{{c}}";
-        let parsed = ZipTemplate::parse(tpl);
-        let mut flat = FxHashMap::default();
-        flat.insert("a".to_string(), "1".to_string());
-        flat.insert("b".to_string(), "2".to_string());
-        flat.insert("c".to_string(), "3".to_string());
-        let out = parsed.render_from_vec(&["1".to_string(), "2".to_string(), "3".to_string()]);
-        assert_eq!(out, "1,2,3");
+    fn changed_placeholders_only_reports_used_keys() {
+        let parsed = ZipTemplate::parse("{{title}}: {{body}} {{title}} {{note ?? body}} {{flag}}");
+        let mut before = FxHashMap::default();
+        before.insert("title".to_string(), "Hi".to_string());
+        before.insert("body".to_string(), "old".to_string());
+        before.insert("unused".to_string(), "x".to_string());
+
+        let mut after = before.clone();
+        after.insert("body".to_string(), "new".to_string());
+        after.insert("unused".to_string(), "y".to_string());
+        assert_eq!(
+            parsed.changed_placeholders(&before, &after),
+            ["body", "note ?? body"]
+        );
+        assert!(parsed.changed_placeholders(&before, &before).is_empty());
+
+        // Appearing with an empty value renders the same but still counts.
+        after.insert("flag".to_string(), String::new());
+        assert_eq!(
+            parsed.changed_placeholders(&before, &after),
+            ["body", "note ?? body", "flag"]
+        );
+        assert_eq!(parsed.changed_placeholders(&after, &before).len(), 3);
     }
 
     #[test]
-    fn empty_template_from_vec() {
-        let tpl = "";
-        let parsed = ZipTemplate::parse(tpl);
-        let out = parsed.render_from_vec(&Vec::new());
-        assert_eq!(out, "");
+    fn render_range_unaligned_fields() {
+        let mut parsed = ZipTemplate::parse("a{{x}}b");
+        parsed.placeholders.truncate(1);
+        let mut values = FxHashMap::default();
+        values.insert("x".to_string(), "1".to_string());
+        assert_eq!(parsed.render_range(&values, 0..2), "a1");
     }
 
     #[test]
-    fn only_static_from_vec() {
-        let tpl = "static text only";
-        let parsed = ZipTemplate::parse(tpl);
-        let out = parsed.render_from_vec(&[]);
-        assert_eq!(out, "static text only");
+    fn filters_apply_to_values() {
+        let parsed = ZipTemplate::parse(
+            "[{{name |> trim |> upper}}] [{{city |> lower | NOWHERE}}] [{{x |> shout}}]",
+        );
+        let mut values = FxHashMap::default();
+        values.insert("name".to_string(), "  sam ".to_string());
+        values.insert("x".to_string(), "as is".to_string());
+        assert_eq!(parsed.render(&values), "[SAM] [nowhere] [as is]");
+
+        let data = serde_json::json!({ "name": " sam ", "x": "as is" });
+        assert_eq!(parsed.render_json(&data), "[SAM] [nowhere] [as is]");
+
+        assert_eq!(
+            ZipTemplate::try_parse("ok {{x |> shout}}").unwrap_err(),
+            ParseError::UnknownFilter {
+                name: "shout".to_string(),
+                span: 3..17
+            }
+        );
+        assert!(ZipTemplate::try_parse("{{x |> trim |> lower}}").is_ok());
+    }
+
+    #[test]
+    fn try_parse_rejects_oversized_specs() {
+        assert_eq!(
+            ZipTemplate::try_parse("n: {{x:999999999999}}").unwrap_err(),
+            ParseError::SpecTooLarge {
+                spec: "999999999999".to_string(),
+                span: 3..21
+            }
+        );
+        assert!(ZipTemplate::try_parse("{{x:.5000}}").is_err());
+        assert!(ZipTemplate::try_parse("{{x:>4096.2}} {{y:group}}").is_ok());
     }
 }
@@ -0,0 +1,139 @@
+//! The built-in `{{@now:format}}` placeholder.
+//!
+//! `@now` is not looked up in the data map: it renders the local time at which the
+//! render started, formatted with the strftime-style spec after the colon. Without
+//! a spec it renders RFC 3339.
+
+use std::fmt;
+
+use chrono::format::StrftimeItems;
+use chrono::{DateTime, Local};
+use rustc_hash::FxHashMap;
+
+use crate::tag::Tag;
+use crate::ZipTemplate;
+
+const NOW_KEY: &str = "@now";
+const DEFAULT_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// Error returned when an `@now` placeholder has a format chrono cannot interpret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NowFormatError {
+    /// The offending format spec.
+    pub format: String,
+}
+
+impl fmt::Display for NowFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid date format `{}` in `{{{{{}:{}}}}}`",
+            self.format, NOW_KEY, self.format
+        )
+    }
+}
+
+impl std::error::Error for NowFormatError {}
+
+impl ZipTemplate {
+    /// Renders a template, resolving `{{@now:format}}` placeholders to the current time.
+    ///
+    /// All `@now` placeholders in one render share the same instant. Every other
+    /// placeholder resolves exactly like [`ZipTemplate::render`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NowFormatError`] if a format spec contains an unknown or malformed
+    /// `%` directive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Report generated {{@now:%Y-%m-%d}}");
+    /// let rendered = template.render_with_now(&FxHashMap::default()).unwrap();
+    /// assert_eq!(rendered.len(), "Report generated 2024-01-01".len());
+    /// ```
+    pub fn render_with_now(
+        &self,
+        flat: &FxHashMap<String, String>,
+    ) -> Result<String, NowFormatError> {
+        self.render_at(flat, &Local::now())
+    }
+
+    fn render_at(
+        &self,
+        flat: &FxHashMap<String, String>,
+        now: &DateTime<Local>,
+    ) -> Result<String, NowFormatError> {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+
+        let resolver = self.resolver(flat);
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+
+            match Tag::parse(placeholder) {
+                Tag {
+                    key: NOW_KEY, spec, ..
+                } => {
+                    let format = spec.unwrap_or(DEFAULT_FORMAT);
+                    let items = StrftimeItems::new(format)
+                        .parse()
+                        .map_err(|_| NowFormatError {
+                            format: format.to_string(),
+                        })?;
+                    out.push_str(&now.format_with_items(items.iter()).to_string());
+                }
+                _ => out.push_str(&resolver.get(placeholder)),
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn now_renders_current_year() {
+        let parsed = ZipTemplate::parse("(c) {{@now:%Y}} {{owner}}");
+        let mut flat = FxHashMap::default();
+        flat.insert("owner".to_string(), "ACME".to_string());
+
+        let now = Local.with_ymd_and_hms(2024, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(parsed.render_at(&flat, &now).unwrap(), "(c) 2024 ACME");
+
+        // The clock may cross a year boundary between these reads.
+        let before = Local::now().format("%Y").to_string();
+        let out = parsed.render_with_now(&flat).unwrap();
+        let after = Local::now().format("%Y").to_string();
+        assert!(
+            [before, after]
+                .iter()
+                .any(|year| out == format!("(c) {} ACME", year)),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn now_format_with_colons() {
+        let parsed = ZipTemplate::parse("{{@now:%Y-%m-%d %H:%M}} / {{ @now }}");
+        let now = Local.with_ymd_and_hms(2024, 3, 9, 7, 5, 0).unwrap();
+        let out = parsed.render_at(&FxHashMap::default(), &now).unwrap();
+        assert_eq!(out, format!("2024-03-09 07:05 / {}", now.to_rfc3339()));
+    }
+
+    #[test]
+    fn now_invalid_format_errors() {
+        let parsed = ZipTemplate::parse("{{@now:%Q}}");
+        let err = parsed.render_with_now(&FxHashMap::default()).unwrap_err();
+        assert_eq!(err.format, "%Q");
+        assert_eq!(err.to_string(), "invalid date format `%Q` in `{{@now:%Q}}`");
+    }
+}
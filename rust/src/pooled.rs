@@ -0,0 +1,223 @@
+//! Templates whose statics live in a shared pool.
+//!
+//! Large template sets often repeat the same static text (a page shell, table row
+//! markup). A [`PooledTemplate`] stores only indices into an `Arc<[String]>` pool,
+//! so each fragment is allocated once no matter how many templates use it.
+
+use std::fmt;
+use std::sync::Arc;
+
+use rustc_hash::FxHashMap;
+
+use crate::{lookup, IndexError, ZipTemplate};
+
+/// Error returned by [`ZipTemplate::with_shared_statics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolError {
+    /// A static index is outside the pool.
+    Index(IndexError),
+    /// There are `placeholders` placeholders for `statics` statics; there must be
+    /// one fewer placeholder than statics, or as many.
+    PlaceholderCount { placeholders: usize, statics: usize },
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Index(err) => err.fmt(f),
+            PoolError::PlaceholderCount {
+                placeholders,
+                statics,
+            } => write!(
+                f,
+                "{} placeholders do not fit between {} statics",
+                placeholders, statics
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+/// A template whose static segments are indices into a shared pool.
+///
+/// Created with [`ZipTemplate::with_shared_statics`].
+#[derive(Debug, Clone)]
+pub struct PooledTemplate {
+    pool: Arc<[String]>,
+    static_indices: Vec<usize>,
+    /// The placeholder keys to be replaced with dynamic values.
+    pub placeholders: Vec<String>,
+}
+
+impl ZipTemplate {
+    /// Creates a template whose statics are borrowed from a shared `pool`.
+    ///
+    /// `static_indices[i]` selects the pool entry used as the `i`-th static segment;
+    /// placeholders follow the same alignment as [`ZipTemplate::parse`] and are
+    /// padded with an empty slot when one shorter than the statics.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PoolError::Index`] for the first index that is outside `pool`, and
+    /// [`PoolError::PlaceholderCount`] unless there are as many placeholders as
+    /// statics or one fewer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let pool: Arc<[String]> = Arc::from(vec!["<p>".to_string(), "</p>".to_string()]);
+    /// let template =
+    ///     ZipTemplate::with_shared_statics(pool, vec![0, 1], vec!["body".to_string()]).unwrap();
+    ///
+    /// let mut values = FxHashMap::default();
+    /// values.insert("body".to_string(), "Hi".to_string());
+    /// assert_eq!(template.render(&values), "<p>Hi</p>");
+    /// ```
+    pub fn with_shared_statics(
+        pool: Arc<[String]>,
+        static_indices: Vec<usize>,
+        mut placeholders: Vec<String>,
+    ) -> Result<PooledTemplate, PoolError> {
+        if let Some(&index) = static_indices.iter().find(|&&i| i >= pool.len()) {
+            return Err(PoolError::Index(IndexError {
+                index,
+                len: pool.len(),
+            }));
+        }
+
+        let statics = static_indices.len();
+        if placeholders.len() + 1 == statics {
+            placeholders.push(String::new());
+        } else if placeholders.len() != statics {
+            return Err(PoolError::PlaceholderCount {
+                placeholders: placeholders.len(),
+                statics,
+            });
+        }
+
+        Ok(PooledTemplate {
+            pool,
+            static_indices,
+            placeholders,
+        })
+    }
+}
+
+impl PooledTemplate {
+    /// The pool the statics are drawn from.
+    pub fn pool(&self) -> &Arc<[String]> {
+        &self.pool
+    }
+
+    /// Iterates the static segments of this template, in order.
+    pub fn statics(&self) -> impl Iterator<Item = &str> + '_ {
+        self.static_indices.iter().map(|&i| self.pool[i].as_str())
+    }
+
+    /// Renders the template like [`ZipTemplate::render`], reading statics from the pool.
+    pub fn render(&self, flat: &FxHashMap<String, String>) -> String {
+        let capacity = self.statics().map(str::len).sum::<usize>() * 3 / 2;
+        let mut out = String::with_capacity(capacity);
+        let dynamics = self
+            .placeholders
+            .iter()
+            .map(|placeholder| lookup(flat, placeholder));
+
+        self.statics().zip(dynamics).for_each(|(s, dynamic)| {
+            out.push_str(s);
+            out.push_str(&dynamic);
+        });
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_share_pooled_statics() {
+        let pool: Arc<[String]> = Arc::from(vec![
+            "<html><body>".to_string(),
+            "</body></html>".to_string(),
+            " | ".to_string(),
+        ]);
+
+        let greeting = ZipTemplate::with_shared_statics(
+            Arc::clone(&pool),
+            vec![0, 1],
+            vec!["greeting".to_string()],
+        )
+        .unwrap();
+        let pair = ZipTemplate::with_shared_statics(
+            Arc::clone(&pool),
+            vec![0, 2, 1],
+            vec!["a".to_string(), "b".to_string()],
+        )
+        .unwrap();
+
+        let mut flat = FxHashMap::default();
+        flat.insert("greeting".to_string(), "Hello".to_string());
+        flat.insert("a".to_string(), "1".to_string());
+        flat.insert("b".to_string(), "2".to_string());
+
+        assert_eq!(greeting.render(&flat), "<html><body>Hello</body></html>");
+        assert_eq!(pair.render(&flat), "<html><body>1 | 2</body></html>");
+        assert!(std::ptr::eq(
+            greeting.statics().next().unwrap(),
+            pair.statics().next().unwrap()
+        ));
+        assert_eq!(Arc::strong_count(&pool), 3);
+    }
+
+    #[test]
+    fn out_of_range_pool_index() {
+        let pool: Arc<[String]> = Arc::from(vec!["a".to_string()]);
+        let err = ZipTemplate::with_shared_statics(pool, vec![0, 1], vec![]).unwrap_err();
+        assert_eq!(err, PoolError::Index(IndexError { index: 1, len: 1 }));
+    }
+
+    #[test]
+    fn too_few_placeholders() {
+        let pool: Arc<[String]> =
+            Arc::from(vec!["A".to_string(), "B".to_string(), "C".to_string()]);
+        let err = ZipTemplate::with_shared_statics(pool, vec![0, 1, 2], vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            PoolError::PlaceholderCount {
+                placeholders: 0,
+                statics: 3
+            }
+        );
+    }
+
+    #[test]
+    fn too_many_placeholders() {
+        let pool: Arc<[String]> = Arc::from(vec!["A".to_string(), "B".to_string()]);
+        let keys = |n: usize| (0..n).map(|i| i.to_string()).collect::<Vec<_>>();
+
+        let err =
+            ZipTemplate::with_shared_statics(Arc::clone(&pool), vec![0, 1], keys(3)).unwrap_err();
+        assert_eq!(
+            err,
+            PoolError::PlaceholderCount {
+                placeholders: 3,
+                statics: 2
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "3 placeholders do not fit between 2 statics"
+        );
+
+        // As many placeholders as statics is the already padded form.
+        let padded = ZipTemplate::with_shared_statics(pool, vec![0, 1], keys(2)).unwrap();
+        assert_eq!(padded.render(&FxHashMap::default()), "AB");
+    }
+}
@@ -0,0 +1,223 @@
+//! Named templates and `{{> partial}}` includes.
+//!
+//! A placeholder whose content starts with `>` is an include tag: instead of being
+//! looked up in the data map it is replaced by the named partial, rendered against
+//! the same data. Includes only resolve through a [`TemplateRegistry`]; the plain
+//! renderers treat them as ordinary (missing) keys.
+
+use std::fmt;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::ZipTemplate;
+
+/// Error returned when resolving includes fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncludeError {
+    /// A `{{> name}}` tag referenced a partial that is not registered.
+    UnknownPartial(String),
+    /// A partial (directly or transitively) includes itself. Holds the name that closed the cycle.
+    Cycle(String),
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncludeError::UnknownPartial(name) => write!(f, "unknown partial `{}`", name),
+            IncludeError::Cycle(name) => write!(f, "include cycle through partial `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for IncludeError {}
+
+/// Returns the partial name if `placeholder` is an include tag (`> name`).
+pub(crate) fn include_name(placeholder: &str) -> Option<&str> {
+    placeholder.strip_prefix('>').map(str::trim)
+}
+
+/// A collection of named templates that can include each other.
+///
+/// # Examples
+///
+/// ```
+/// use zip_templates::{TemplateRegistry, ZipTemplate};
+/// use rustc_hash::FxHashMap;
+///
+/// let mut registry = TemplateRegistry::new();
+/// registry.register("header", ZipTemplate::parse("<h1>{{title}}</h1>"));
+/// registry.register("page", ZipTemplate::parse("{{> header}}<p>{{body}}</p>"));
+///
+/// let mut values = FxHashMap::default();
+/// values.insert("title".to_string(), "Home".to_string());
+/// values.insert("body".to_string(), "Welcome".to_string());
+///
+/// let rendered = registry.render("page", &values).unwrap();
+/// assert_eq!(rendered, "<h1>Home</h1><p>Welcome</p>");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRegistry {
+    templates: FxHashMap<String, ZipTemplate>,
+}
+
+impl TemplateRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `template` under `name`, returning the template it replaced, if any.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        template: ZipTemplate,
+    ) -> Option<ZipTemplate> {
+        self.templates.insert(name.into(), template)
+    }
+
+    /// Returns the template registered under `name`.
+    pub fn get(&self, name: &str) -> Option<&ZipTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Renders the template registered under `name`, resolving its includes.
+    pub fn render(
+        &self,
+        name: &str,
+        flat: &FxHashMap<String, String>,
+    ) -> Result<String, IncludeError> {
+        let template = self
+            .get(name)
+            .ok_or_else(|| IncludeError::UnknownPartial(name.to_string()))?;
+
+        let mut visiting = FxHashSet::default();
+        visiting.insert(name);
+
+        let mut out = String::with_capacity(template.pre_emptive_size);
+        self.render_into(template, flat, &mut visiting, &mut out)?;
+        Ok(out)
+    }
+
+    fn render_into<'a>(
+        &'a self,
+        template: &'a ZipTemplate,
+        flat: &FxHashMap<String, String>,
+        visiting: &mut FxHashSet<&'a str>,
+        out: &mut String,
+    ) -> Result<(), IncludeError> {
+        let resolver = template.resolver(flat);
+        for (s, placeholder) in template.statics.iter().zip(template.placeholders.iter()) {
+            out.push_str(s);
+
+            match include_name(placeholder) {
+                Some(name) => {
+                    let (name, partial) = self
+                        .templates
+                        .get_key_value(name)
+                        .ok_or_else(|| IncludeError::UnknownPartial(name.to_string()))?;
+
+                    // Only the current include chain counts: the same partial may
+                    // appear several times side by side without forming a cycle.
+                    if !visiting.insert(name.as_str()) {
+                        return Err(IncludeError::Cycle(name.clone()));
+                    }
+                    self.render_into(partial, flat, visiting, out)?;
+                    visiting.remove(name.as_str());
+                }
+                None => out.push_str(&resolver.get(placeholder)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ZipTemplate {
+    /// Renders the template, resolving `{{> name}}` includes through `registry`.
+    ///
+    /// Each partial is rendered against the same `flat` map and spliced in place of
+    /// its include tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IncludeError::UnknownPartial`] if an include names a template that is
+    /// not registered, and [`IncludeError::Cycle`] if partials include each other recursively.
+    pub fn render_with_registry(
+        &self,
+        registry: &TemplateRegistry,
+        flat: &FxHashMap<String, String>,
+    ) -> Result<String, IncludeError> {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        registry.render_into(self, flat, &mut FxHashSet::default(), &mut out)?;
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> FxHashMap<String, String> {
+        let mut flat = FxHashMap::default();
+        flat.insert("name".to_string(), "Sam".to_string());
+        flat
+    }
+
+    #[test]
+    fn include_tag_parses_as_placeholder() {
+        let parsed = ZipTemplate::parse("a{{>  header }}b");
+        assert_eq!(parsed.placeholders, [">  header", ""]);
+        assert_eq!(include_name(&parsed.placeholders[0]), Some("header"));
+        assert_eq!(include_name("name"), None);
+    }
+
+    #[test]
+    fn simple_partial() {
+        let mut registry = TemplateRegistry::new();
+        registry.register("greet", ZipTemplate::parse("Hello, {{name}}"));
+        let page = ZipTemplate::parse("[{{> greet}}] [{{> greet}}]");
+        let out = page.render_with_registry(&registry, &values()).unwrap();
+        assert_eq!(out, "[Hello, Sam] [Hello, Sam]");
+    }
+
+    #[test]
+    fn nested_partials() {
+        let mut registry = TemplateRegistry::new();
+        registry.register("inner", ZipTemplate::parse("<{{name}}>"));
+        registry.register("outer", ZipTemplate::parse("({{> inner}})"));
+        registry.register("page", ZipTemplate::parse("{{> outer}}!"));
+        assert_eq!(registry.render("page", &values()).unwrap(), "(<Sam>)!");
+    }
+
+    #[test]
+    fn unknown_partial() {
+        let registry = TemplateRegistry::new();
+        let page = ZipTemplate::parse("{{> missing}}");
+        assert_eq!(
+            page.render_with_registry(&registry, &values()),
+            Err(IncludeError::UnknownPartial("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn self_include_cycle() {
+        let mut registry = TemplateRegistry::new();
+        registry.register("loop", ZipTemplate::parse("again {{> loop}}"));
+        assert_eq!(
+            registry.render("loop", &values()),
+            Err(IncludeError::Cycle("loop".to_string()))
+        );
+    }
+
+    #[test]
+    fn mutual_include_cycle() {
+        let mut registry = TemplateRegistry::new();
+        registry.register("a", ZipTemplate::parse("{{> b}}"));
+        registry.register("b", ZipTemplate::parse("{{> a}}"));
+        let page = ZipTemplate::parse("{{> a}}");
+        assert_eq!(
+            page.render_with_registry(&registry, &values()),
+            Err(IncludeError::Cycle("a".to_string()))
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Checking templates against a JSON Schema describing their data.
+//!
+//! Only the structural subset of JSON Schema is interpreted: `properties`,
+//! `required`, `items` and `default`. Placeholder paths are walked through the
+//! schema one dot-separated segment at a time.
+//!
+//! The reverse direction, [`ZipTemplate::to_schema_skeleton`], builds an example
+//! document with the shape a template expects.
+
+use serde_json::{Map, Value};
+
+use crate::tag::{is_sigil_tag, Tag};
+use crate::ZipTemplate;
+
+/// A problem found by [`validate_against_schema`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaIssue {
+    /// `placeholder` walks into `path`, which the schema does not define.
+    UndefinedProperty { placeholder: String, path: String },
+    /// `placeholder` reads the optional property `path`, which has no `default`
+    /// in the schema and no `??` fallback or `|default` in the template.
+    OptionalWithoutDefault { placeholder: String, path: String },
+}
+
+/// Checks every placeholder of `template` against `schema`.
+///
+/// A placeholder is flagged when its path leaves the properties the schema
+/// declares, or when it passes through a property that is not `required` and
+/// has no `default` while the placeholder has no `??` fallback or `|default`. Array indices
+/// (`items.0`) resolve through `items` and are not checked for presence. Include,
+/// control, comment, built-in and inline tags are skipped, as in
+/// [`ZipTemplate::to_schema_skeleton`], and format specs are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use zip_templates::{validate_against_schema, SchemaIssue, ZipTemplate};
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": { "name": { "type": "string" } },
+///     "required": ["name"]
+/// });
+/// let template = ZipTemplate::parse("{{name}} <{{email}}>");
+///
+/// assert_eq!(
+///     validate_against_schema(&template, &schema),
+///     [SchemaIssue::UndefinedProperty {
+///         placeholder: "email".to_string(),
+///         path: "email".to_string(),
+///     }]
+/// );
+/// ```
+pub fn validate_against_schema(template: &ZipTemplate, schema: &Value) -> Vec<SchemaIssue> {
+    let mut issues = Vec::new();
+
+    for placeholder in template.unique_placeholders() {
+        if is_sigil_tag(placeholder) {
+            continue;
+        }
+
+        let tag = Tag::parse(placeholder);
+        let has_fallback = tag.key.contains("??") || tag.default.is_some();
+        for key in tag.keys() {
+            if let Some(issue) = check_path(placeholder, &key, schema, has_fallback) {
+                issues.push(issue);
+            }
+        }
+    }
+
+    issues
+}
+
+impl ZipTemplate {
+    /// Builds an empty JSON document with the shape this template reads: every
+    /// placeholder path becomes a `null` leaf inside nested objects.
+    ///
+    /// Numeric segments (`items.0`) create arrays, padded with `null` up to the
+    /// index. Indices above 1024 are kept as object keys instead, so a template
+    /// can not make the skeleton arbitrarily large. Every key of a `??` chain is
+    /// included; include tags, control tags and other sigil tags are skipped. When
+    /// a path is also the prefix of another, the nested object wins over the `null`
+    /// leaf.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use zip_templates::ZipTemplate;
+    ///
+    /// let template = ZipTemplate::parse("{{user.name}} ({{user.age}})");
+    /// assert_eq!(
+    ///     template.to_schema_skeleton(),
+    ///     json!({ "user": { "name": null, "age": null } })
+    /// );
+    /// ```
+    pub fn to_schema_skeleton(&self) -> Value {
+        let mut root = Value::Object(Map::new());
+
+        for placeholder in self.unique_placeholders() {
+            if is_sigil_tag(placeholder) {
+                continue;
+            }
+            for key in Tag::parse(placeholder).keys() {
+                let segments: Vec<&str> = key.split('.').collect();
+                insert_path(&mut root, &segments);
+            }
+        }
+
+        root
+    }
+}
+
+/// The largest numeric segment [`insert_path`] turns into an array index.
+const MAX_ARRAY_INDEX: usize = 1024;
+
+/// Parses `segment` as an array index no larger than [`MAX_ARRAY_INDEX`].
+fn array_index(segment: &str) -> Option<usize> {
+    segment
+        .parse::<usize>()
+        .ok()
+        .filter(|&index| index <= MAX_ARRAY_INDEX)
+}
+
+/// Creates the containers along `segments` under `node`, ending in a `null` leaf
+/// unless something is already there.
+fn insert_path(node: &mut Value, segments: &[&str]) {
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+    let index = array_index(segment);
+
+    // A small numeric segment may index an array; anything else needs an object,
+    // so an array reached by a name is turned into an object keyed by index.
+    if let (Value::Array(items), None) = (&*node, index) {
+        let map = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (i.to_string(), item.clone()))
+            .collect();
+        *node = Value::Object(map);
+    }
+
+    let child = match (node, index) {
+        (Value::Array(items), Some(index)) => {
+            if items.len() <= index {
+                items.resize(index + 1, Value::Null);
+            }
+            &mut items[index]
+        }
+        (Value::Object(map), _) => map.entry(segment.to_string()).or_insert(Value::Null),
+        _ => return,
+    };
+
+    if child.is_null() {
+        match rest.first() {
+            Some(next) if array_index(next).is_some() => *child = Value::Array(Vec::new()),
+            Some(_) => *child = Value::Object(Map::new()),
+            None => {}
+        }
+    }
+    insert_path(child, rest);
+}
+
+fn check_path(
+    placeholder: &str,
+    key: &str,
+    schema: &Value,
+    has_fallback: bool,
+) -> Option<SchemaIssue> {
+    let mut node = schema;
+    let mut optional_path = None;
+
+    for (end, segment) in segments(key) {
+        let path = &key[..end];
+
+        if let Some(child) = node.get("properties").and_then(|p| p.get(segment)) {
+            let required = node
+                .get("required")
+                .and_then(Value::as_array)
+                .is_some_and(|r| r.iter().any(|name| name == segment));
+            if !required && child.get("default").is_none() && optional_path.is_none() {
+                optional_path = Some(path);
+            }
+            node = child;
+        } else if let (Some(items), true) = (node.get("items"), segment.parse::<usize>().is_ok()) {
+            node = items;
+        } else {
+            return Some(SchemaIssue::UndefinedProperty {
+                placeholder: placeholder.to_string(),
+                path: path.to_string(),
+            });
+        }
+    }
+
+    match optional_path {
+        Some(path) if !has_fallback => Some(SchemaIssue::OptionalWithoutDefault {
+            placeholder: placeholder.to_string(),
+            path: path.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// Yields each dot-separated segment of `key` with the byte offset where it ends.
+fn segments(key: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut start = 0;
+    key.split('.').map(move |segment| {
+        let end = start + segment.len();
+        start = end + 1;
+        (end, segment)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "user": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "nickname": { "type": "string" },
+                        "locale": { "type": "string", "default": "en" },
+                        "tags": { "type": "array", "items": { "type": "string" } }
+                    },
+                    "required": ["name", "tags"]
+                }
+            },
+            "required": ["user"]
+        })
+    }
+
+    #[test]
+    fn valid_paths_have_no_issues() {
+        let template = ZipTemplate::parse(
+            "{{user.name}} {{user.locale}} {{user.tags.0}} {{user.nickname ?? user.name}}",
+        );
+        assert_eq!(validate_against_schema(&template, &schema()), []);
+    }
+
+    #[test]
+    fn undefined_property_reference() {
+        let template = ZipTemplate::parse("{{user.email}} {{user.name.first}} {{account}}");
+        assert_eq!(
+            validate_against_schema(&template, &schema()),
+            [
+                SchemaIssue::UndefinedProperty {
+                    placeholder: "user.email".to_string(),
+                    path: "user.email".to_string(),
+                },
+                SchemaIssue::UndefinedProperty {
+                    placeholder: "user.name.first".to_string(),
+                    path: "user.name.first".to_string(),
+                },
+                SchemaIssue::UndefinedProperty {
+                    placeholder: "account".to_string(),
+                    path: "account".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sigil_tags_are_not_validated() {
+        let template = ZipTemplate::parse(
+            "{{#each user.tags}}{{/each}} {{@now:%Y}} {{!note}} {{*user}} {{> footer}} {{user.name}}",
+        );
+        assert_eq!(validate_against_schema(&template, &schema()), []);
+        assert_eq!(
+            template.to_schema_skeleton(),
+            json!({ "user": { "name": null } })
+        );
+    }
+
+    #[test]
+    fn optional_without_default() {
+        let template = ZipTemplate::parse("Hi {{user.nickname}}");
+        assert_eq!(
+            validate_against_schema(&template, &schema()),
+            [SchemaIssue::OptionalWithoutDefault {
+                placeholder: "user.nickname".to_string(),
+                path: "user.nickname".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn schema_skeleton_shape() {
+        let template = ZipTemplate::parse(
+            "{{user.name}} {{user.address.city}} {{items.1.sku}} {{items.0.qty:>4}} \
+             {{nick ?? user.login | anon}} {{> footer}} {{#each items}}{{/each}} {{user.name}}",
+        );
+        assert_eq!(
+            template.to_schema_skeleton(),
+            json!({
+                "user": { "name": null, "address": { "city": null }, "login": null },
+                "items": [{ "qty": null }, { "sku": null }],
+                "nick": null
+            })
+        );
+
+        let overlapping = ZipTemplate::parse("{{a}} {{a.b}} {{list.0}} {{list.name}}");
+        assert_eq!(
+            overlapping.to_schema_skeleton(),
+            json!({ "a": { "b": null }, "list": { "0": null, "name": null } })
+        );
+    }
+
+    #[test]
+    fn schema_skeleton_huge_indices_become_keys() {
+        let template = ZipTemplate::parse("{{a.18446744073709551615}} {{b.4000000000.c}} {{b.1}}");
+        assert_eq!(
+            template.to_schema_skeleton(),
+            json!({
+                "a": { "18446744073709551615": null },
+                "b": { "4000000000": { "c": null }, "1": null }
+            })
+        );
+
+        let bounded = ZipTemplate::parse("{{a.1024}}").to_schema_skeleton();
+        assert_eq!(bounded["a"].as_array().map(Vec::len), Some(1025));
+    }
+}
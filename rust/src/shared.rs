@@ -0,0 +1,132 @@
+//! Values shared by every template of a bundle.
+//!
+//! Global values such as `site.name` or `year` are used by most templates of a
+//! page. [`SharedValues`] stringifies them once so each render only supplies the
+//! values specific to it.
+
+use rustc_hash::FxHashMap;
+use serde_json::Value;
+
+use crate::{flatten_json_visit, ZipTemplate};
+
+/// Pre-resolved values consulted before the per-render map, see
+/// [`ZipTemplate::render_with_shared`].
+#[derive(Debug, Clone, Default)]
+pub struct SharedValues {
+    values: FxHashMap<String, String>,
+}
+
+impl SharedValues {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Flattens `value` like [`crate::flatten_json`] and caches every leaf.
+    pub fn from_json(value: &Value) -> Self {
+        let mut shared = SharedValues::new();
+        flatten_json_visit(value, |key, value| {
+            shared.insert(key, value);
+        });
+        shared
+    }
+
+    /// Caches the string form of `value` under `key`.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl ToString) {
+        self.values.insert(key.into(), value.to_string());
+    }
+
+    /// Returns the cached value for `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+}
+
+impl ZipTemplate {
+    /// Renders a template, resolving placeholders from `shared` first and `extra` second.
+    ///
+    /// Placeholders resolve like [`ZipTemplate::render`], with every key looked up
+    /// in `shared` before `extra`, including the keys of `??` chains and tags with
+    /// a `|default`, spec or filters. Shared values therefore take precedence over
+    /// per-render ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::{SharedValues, ZipTemplate};
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let mut shared = SharedValues::new();
+    /// shared.insert("year", 2024);
+    ///
+    /// let template = ZipTemplate::parse("{{title}} (c) {{year}}");
+    /// let mut extra = FxHashMap::default();
+    /// extra.insert("title".to_string(), "Home".to_string());
+    ///
+    /// assert_eq!(template.render_with_shared(&shared, &extra), "Home (c) 2024");
+    /// ```
+    pub fn render_with_shared(
+        &self,
+        shared: &SharedValues,
+        extra: &FxHashMap<String, String>,
+    ) -> String {
+        let mut out = String::with_capacity(self.pre_emptive_size);
+        let resolver = self.resolver(extra);
+
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+            out.push_str(&resolver.get_over(placeholder, |key| shared.get(key)));
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn shared_value_used_by_two_templates() {
+        let shared = SharedValues::from_json(&json!({
+            "site": { "name": "Example" },
+            "year": 2024
+        }));
+
+        let header = ZipTemplate::parse("<h1>{{site.name}}: {{title}}</h1>");
+        let footer = ZipTemplate::parse("<p>(c) {{year}} {{site.name}}, {{author}}</p>");
+
+        let mut header_extra = FxHashMap::default();
+        header_extra.insert("title".to_string(), "Home".to_string());
+        let mut footer_extra = FxHashMap::default();
+        footer_extra.insert("author".to_string(), "Sam".to_string());
+
+        assert_eq!(
+            header.render_with_shared(&shared, &header_extra),
+            "<h1>Example: Home</h1>"
+        );
+        assert_eq!(
+            footer.render_with_shared(&shared, &footer_extra),
+            "<p>(c) 2024 Example, Sam</p>"
+        );
+    }
+
+    #[test]
+    fn shared_values_in_tags() {
+        let mut shared = SharedValues::new();
+        shared.insert("site", "Example");
+        shared.insert("empty", "");
+
+        let template =
+            ZipTemplate::parse("{{site ?? x}} {{empty ?? title}} {{site |> upper}} {{none | -}}");
+        let mut extra = FxHashMap::default();
+        extra.insert("title".to_string(), "Home".to_string());
+        extra.insert("site".to_string(), "ignored".to_string());
+
+        assert_eq!(
+            template.render_with_shared(&shared, &extra),
+            "Example Home EXAMPLE -"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Rendering into an inline buffer.
+//!
+//! Many tiny renders spend more time allocating their output than producing it.
+//! A `SmallString` keeps up to `N` bytes inline and only moves to the heap when the
+//! output is longer.
+
+use rustc_hash::FxHashMap;
+use smallstr::SmallString;
+
+use crate::ZipTemplate;
+
+impl ZipTemplate {
+    /// Renders a template into a [`SmallString`] holding up to `N` bytes inline.
+    ///
+    /// Resolution is identical to [`ZipTemplate::render`]. An output of at most `N`
+    /// bytes never allocates; a longer one spills to the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zip_templates::ZipTemplate;
+    /// use rustc_hash::FxHashMap;
+    ///
+    /// let template = ZipTemplate::parse("Hello, {{name}}!");
+    /// let mut values = FxHashMap::default();
+    /// values.insert("name".to_string(), "World".to_string());
+    ///
+    /// let rendered = template.render_smallstring::<32>(&values);
+    /// assert_eq!(rendered, "Hello, World!");
+    /// assert!(!rendered.spilled());
+    /// ```
+    pub fn render_smallstring<const N: usize>(
+        &self,
+        flat: &FxHashMap<String, String>,
+    ) -> SmallString<[u8; N]> {
+        let mut out = SmallString::new();
+
+        let resolver = self.resolver(flat);
+        for (s, placeholder) in self.statics.iter().zip(self.placeholders.iter()) {
+            out.push_str(s);
+            out.push_str(&resolver.get(placeholder));
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_and_spilled() {
+        let parsed = ZipTemplate::parse("id={{id}}");
+        let mut values = FxHashMap::default();
+
+        values.insert("id".to_string(), "42".to_string());
+        let short = parsed.render_smallstring::<16>(&values);
+        assert_eq!(short, "id=42");
+        assert!(!short.spilled());
+
+        values.insert("id".to_string(), "4".repeat(40));
+        let long = parsed.render_smallstring::<16>(&values);
+        assert_eq!(long.as_str(), parsed.render(&values));
+        assert!(long.spilled());
+    }
+}
@@ -0,0 +1,208 @@
+//! The sub-syntax inside a placeholder.
+//!
+//! A placeholder's content has the shape `key [:spec] [|> filter]* [|default]`:
+//!
+//! - `key` is a dot-path, optionally a `??` chain of fallback paths, optionally
+//!   prefixed by `?` to make the surrounding statics conditional;
+//! - `spec` (after the first `:`) is interpreted by the formatting renderers;
+//! - each `filter` (after a `|>`) names a transformation applied to the value:
+//!   `trim`, `upper` or `lower`; unknown names leave the value unchanged;
+//! - `default` (after the first `|` not starting a `|>`) is used when every key
+//!   resolves empty.
+//!
+//! A literal `|`, `:` or `\` inside any part is written `\|`, `\:` or `\\`.
+
+use std::borrow::Cow;
+
+/// A placeholder split into its parts. All parts are still escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Tag<'a> {
+    pub key: &'a str,
+    pub spec: Option<&'a str>,
+    pub filters: Option<&'a str>,
+    pub default: Option<&'a str>,
+}
+
+impl<'a> Tag<'a> {
+    pub fn parse(placeholder: &'a str) -> Self {
+        let (head, default) = split_unescaped(placeholder, |rest| {
+            (rest.starts_with('|') && !rest.starts_with("|>")).then_some(1)
+        });
+        let (head, filters) = split_unescaped(head, |rest| rest.starts_with("|>").then_some(2));
+        let (key, spec) = split_unescaped(head, |rest| rest.starts_with(':').then_some(1));
+        Tag {
+            key,
+            spec,
+            filters,
+            default,
+        }
+    }
+
+    /// The names of the `|>` filters, in the order they apply.
+    pub fn filters(&self) -> impl Iterator<Item = &'a str> {
+        self.filters
+            .into_iter()
+            .flat_map(|filters| filters.split("|>"))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Applies the `|>` filters to `value` in order, skipping unknown names.
+    pub fn apply_filters<'v>(&self, value: Cow<'v, str>) -> Cow<'v, str> {
+        self.filters().fold(value, |value, name| match name {
+            "trim" => match value {
+                Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+                Cow::Owned(s) => Cow::Owned(s.trim().to_string()),
+            },
+            "upper" => Cow::Owned(value.to_uppercase()),
+            "lower" => Cow::Owned(value.to_lowercase()),
+            _ => value,
+        })
+    }
+
+    /// Returns `true` for `{{?key}}` tags, whose neighbouring statics are dropped
+    /// when the value is empty.
+    pub fn is_conditional(&self) -> bool {
+        self.key.starts_with('?') && !self.key.starts_with("??")
+    }
+
+    /// The unescaped keys of the `??` chain, in order.
+    pub fn keys(&self) -> impl Iterator<Item = Cow<'a, str>> {
+        let key = match self.is_conditional() {
+            true => &self.key[1..],
+            false => self.key,
+        };
+        key.split("??").map(|key| unescape(key.trim()))
+    }
+}
+
+/// Returns `true` if `name` is a filter [`Tag::apply_filters`] knows.
+pub(crate) fn is_filter(name: &str) -> bool {
+    matches!(name, "trim" | "upper" | "lower")
+}
+
+/// Returns `true` for tags that are not data paths: includes (`>`), control tags
+/// (`#`, `/`), comments (`!`), built-ins (`@`) and inline subtrees (`*`).
+pub(crate) fn is_sigil_tag(placeholder: &str) -> bool {
+    placeholder.starts_with(['#', '/', '>', '!', '@', '*'])
+}
+
+/// Splits `s` around the first separator not preceded by a `\`, trimming whitespace
+/// next to it. `separator` is tried at each unescaped position and returns the
+/// separator's length in bytes if one starts there.
+fn split_unescaped(s: &str, separator: impl Fn(&str) -> Option<usize>) -> (&str, Option<&str>) {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ => {
+                if let Some(len) = separator(&s[i..]) {
+                    return (s[..i].trim_end(), Some(s[i + len..].trim_start()));
+                }
+            }
+        }
+    }
+    (s, None)
+}
+
+/// Removes the backslash from `\|`, `\:` and `\\` sequences.
+pub(crate) fn unescape(s: &str) -> Cow<'_, str> {
+    if !s.contains('\\') {
+        return Cow::Borrowed(s);
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next @ ('|' | ':' | '\\')) => out.push(next),
+                Some(other) => {
+                    out.push('\\');
+                    out.push(other);
+                }
+                None => out.push('\\'),
+            },
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_parts() {
+        assert_eq!(
+            Tag::parse("amount : group | n/a"),
+            Tag {
+                key: "amount",
+                spec: Some("group"),
+                filters: None,
+                default: Some("n/a")
+            }
+        );
+        assert_eq!(
+            Tag::parse("time:%H:%M"),
+            Tag {
+                key: "time",
+                spec: Some("%H:%M"),
+                filters: None,
+                default: None
+            }
+        );
+        assert_eq!(
+            Tag::parse("clock|12:00"),
+            Tag {
+                key: "clock",
+                spec: None,
+                filters: None,
+                default: Some("12:00")
+            }
+        );
+    }
+
+    #[test]
+    fn escaped_separators() {
+        let tag = Tag::parse(r"x|a\|b");
+        assert_eq!(tag.key, "x");
+        assert_eq!(tag.default.map(unescape).as_deref(), Some("a|b"));
+
+        let tag = Tag::parse(r"ns\:key:group");
+        assert_eq!(tag.keys().collect::<Vec<_>>(), ["ns:key"]);
+        assert_eq!(tag.spec, Some("group"));
+
+        assert_eq!(unescape(r"a\\b\n"), r"a\b\n");
+    }
+
+    #[test]
+    fn fallback_chain_keys() {
+        let tag = Tag::parse("a ?? b ?? c | none");
+        assert_eq!(tag.keys().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(tag.default, Some("none"));
+    }
+
+    #[test]
+    fn conditional_prefix() {
+        let tag = Tag::parse("? middle ?? nick");
+        assert!(tag.is_conditional());
+        assert_eq!(tag.keys().collect::<Vec<_>>(), ["middle", "nick"]);
+        assert!(!Tag::parse("middle").is_conditional());
+    }
+
+    #[test]
+    fn filter_chain() {
+        let tag = Tag::parse("name:<10 |> trim |> upper | anonymous");
+        assert_eq!(tag.keys().collect::<Vec<_>>(), ["name"]);
+        assert_eq!(tag.spec, Some("<10"));
+        assert_eq!(tag.filters().collect::<Vec<_>>(), ["trim", "upper"]);
+        assert_eq!(tag.default, Some("anonymous"));
+
+        let tag = Tag::parse(r"x|a\|>b");
+        assert_eq!(tag.filters().count(), 0);
+        assert_eq!(tag.default.map(unescape).as_deref(), Some("a|>b"));
+    }
+}